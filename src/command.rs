@@ -2,17 +2,141 @@
 // See the LICENSE file for full license details.
 
 
-use clap::{arg, Command};
+use clap::{arg, Arg, ArgAction, ArgGroup, Command};
 
-pub fn main() -> [Command; 3] {
+pub fn main() -> [Command; 13] {
     return [
         Command::new("send")
             .about("Parse and then send the .mcq file to telegram.")
-            .arg(arg!(<FILE> "The .mcq file path. (e.g. \"./dir/to/test.mcq\")")),
+            .arg(arg!(<FILE> "The .mcq file path. (e.g. \"./dir/to/test.mcq\")"))
+            .arg(arg!(--force "Send the file even if it was already sent to the chat."))
+            .arg(arg!(--"new-only" "Send only the sections that were not sent to the chat before.").conflicts_with("force"))
+            .arg(arg!(--at <TIME> "Wait and send the file at the time, it must have an offset or `Z` for UTC. (e.g. \"2026-11-02T08:00+03:00\")"))
+            .arg(arg!(--"close-after" <DURATION> "Close the polls/questions after the duration and send the results. (e.g. \"10m\")"))
+            .args(filter_args()),
+        Command::new("exam")
+            .about("Send the .mcq file as a timed exam, one section at a time.")
+            .long_about(
+                "Send the .mcq file as a timed exam, one section at a time. Every poll/question will be \
+                open for the interval, and the next section will be sent after it is closed. If the exam \
+                stopped, run the same command to resume it."
+            )
+            .arg(arg!(<FILE> "The .mcq file path. (e.g. \"./dir/to/test.mcq\")"))
+            .arg(arg!(--interval <DURATION> "The time of every poll/question, between 5s and 10m. (e.g. \"45s\")").required(true))
+            .arg(arg!(--"answer-key" "Send the answer key message at the end of the exam."))
+            .arg(arg!(--restart "Start the exam from the beginning instead of resuming it."))
+            .args(filter_args()),
+        Command::new("schedule")
+            .about("Queue the .mcq files to be sent at a specific time.")
+            .subcommand_required(true)
+            .subcommands([
+                Command::new("add")
+                    .about("Queue a copy of the .mcq file to be sent at the time.")
+                    .arg(arg!(<FILE> "The .mcq file path. (e.g. \"./dir/to/test.mcq\")"))
+                    .arg(arg!(--at <TIME> "The send time, it must have an offset or `Z` for UTC. (e.g. \"2026-11-02T08:00+03:00\")").required(true))
+                    .arg(arg!(--force "Queue the file even if it was already sent to the chat."))
+                    .args(filter_args()),
+                Command::new("list")
+                    .about("List the queued files."),
+                Command::new("cancel")
+                    .about("Remove the queued file from the queue.")
+                    .arg(arg!(<ID> "The queued file id, see `mcqp schedule list`.")),
+                Command::new("run")
+                    .about("Keep running and send the queued files at their times.")
+            ]),
+        Command::new("serve")
+            .about("Run a quiz bot that sends the .mcq file to the users in private chats.")
+            .long_about(
+                "Run a quiz bot that sends the .mcq file to the users in private chats. The users \
+                send /quiz to the bot to start, and the next question is sent after they answer \
+                the current one. It uses the bot updates, so do not run `mcqp results` at the same time."
+            )
+            .arg(arg!(<FILE> "The .mcq file path. (e.g. \"./dir/to/test.mcq\")"))
+            .args(filter_args()),
+        Command::new("practice")
+            .about("Take the quiz in the terminal, without telegram.")
+            .long_about(
+                "Take the quiz in the terminal, without telegram. Only the questions are asked, \
+                the polls and the messages are skipped. The wrong answered questions are saved, \
+                so they can be practiced again with `--wrong-only`."
+            )
+            .arg(arg!(<FILE> "The .mcq file path. (e.g. \"./dir/to/test.mcq\")"))
+            .arg(arg!(--shuffle "Shuffle the questions and their choices."))
+            .arg(arg!(--"wrong-only" "Ask only the questions that were answered wrong in the last practices."))
+            .args(filter_args()),
+        Command::new("study")
+            .about("Study the questions in the terminal with spaced repetition.")
+            .long_about(
+                "Study the questions in the terminal with spaced repetition. The due questions are \
+                asked first and then the new ones, and every question is scheduled again by how well \
+                it was answered (SM-2). The progress is saved by the question content, so it is shared \
+                between the files."
+            )
+            .arg(arg!(<FILE> "The .mcq file path. (e.g. \"./dir/to/test.mcq\")"))
+            .arg(arg!(--new <NUMBER> "The maximum number of the new questions in the session. (default: 20)"))
+            .arg(arg!(--stats "Show the study progress of the file instead of studying."))
+            .args(filter_args()),
+        Command::new("export")
+            .about("Export the .mcq file to another format.")
+            .arg(arg!(<FILE> "The .mcq file path. (e.g. \"./dir/to/test.mcq\")"))
+            .arg(
+                arg!(--format <FORMAT> "The output format, `anki` is the Anki tab-separated import file, \
+                    and `html`, `md` and `latex` are printable exams with an answer key.")
+                    .value_parser(["anki", "html", "md", "latex"])
+                    .required(true)
+            )
+            .arg(arg!(-o --output <FILE> "The output file, the default is the .mcq file path with the format extension."))
+            .arg(arg!(--"two-columns" "Print the exam in two columns (html and latex only)."))
+            .args(filter_args()),
         Command::new("check")
             .about("Check if there any syntax errors.")
-            .arg(arg!(<FILE> "The .mcq file path. (e.g. \"./dir/to/test.mcq\")")),
+            .arg(arg!(<FILE> "The .mcq file path. (e.g. \"./dir/to/test.mcq\")"))
+            .args(filter_args()),
         Command::new("config")
-            .about("Configure the bot-token and chat-id.")
+            .about("Configure the bot-token and chat-id."),
+        Command::new("sync")
+            .about("Update the sent sections in the chat to match the .mcq file.")
+            .long_about(
+                "Update the sent sections in the chat to match the .mcq file. The changed messages \
+                will be edited, the changed polls/questions will be deleted and sent again \
+                (they will be at the end of the chat), and the new sections will be sent. The sections \
+                are matched by the `// @id:` metadata or by the section number if there is no id."
+            )
+            .arg(arg!(<FILE> "The .mcq file path. (e.g. \"./dir/to/test.mcq\")"))
+            .arg(arg!(--prune "Delete the sent sections that are not in the file anymore."))
+            .arg(arg!(--"dry-run" "Show the changes without sending anything."))
+            .args(filter_args()),
+        Command::new("unsend")
+            .about("Delete all messages and polls of a send run from the chat.")
+            .arg(arg!(--last "Delete the last send run."))
+            .arg(arg!(--run <ID> "Delete the send run with this id, see `mcqp history`."))
+            .arg(arg!(-y --yes "Do not ask for confirmation."))
+            .group(ArgGroup::new("target").args(["last", "run"]).required(true)),
+        Command::new("results")
+            .about("Collect the answers of the non-anonymous questions and grade the users.")
+            .arg(arg!(--run <ID> "The send run of the questions, the default is the last run."))
+            .arg(arg!(--updates <FILE> "Read the answers from a saved getUpdates dump instead of telegram."))
+            .arg(arg!(--timeout <SECONDS> "The long polling timeout in seconds. (default: 5)"))
+            .arg(
+                arg!(--format <FORMAT> "The output format.")
+                    .value_parser(["csv", "json"])
+                    .default_value("csv")
+            )
+            .arg(arg!(-o --output <FILE> "Write the results to the file instead of the terminal.")),
+        Command::new("history")
+            .about("List the sent sections, what was sent where and when.")
+            .arg(arg!(--run <ID> "Show the sections of this run only."))
+            .arg(arg!(--chat <CHAT_ID> "Show the runs of this chat only."))
+            .arg(arg!(-v --verbose "Show the sections of every run."))
     ];
-}
\ No newline at end of file
+}
+
+/// The sections filter arguments.
+fn filter_args() -> [Arg; 3] {
+    return [
+        arg!(--tag <TAG> "Only the sections that have this tag, repeat it to require more tags. (e.g. \"--tag algebra\")")
+            .action(ArgAction::Append),
+        arg!(--difficulty <DIFFICULTY> "Only the sections that have this difficulty. (e.g. \"--difficulty hard\")"),
+        arg!(--only <RANGE> "Only the sections in this range, the first section is 1. (e.g. \"--only 5..20\")")
+    ];
+}
@@ -8,7 +8,7 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 
-use crate::{display::Display, log::Log, utils};
+use crate::{display::Display, log::Log, results, telegram, utils};
 
 #[derive(Serialize, Deserialize)]
 pub struct Config {
@@ -44,7 +44,9 @@ pub struct ChatResult {
     /// - `chat`: The chat that send the message
     /// - `date`: Sended at
     /// - `text`: The message body
-    message: Message
+    /// 
+    /// It is `None` if the update is not a message (e.g. `poll_answer`).
+    message: Option<Message>
 }
 
 /// The message extractor.
@@ -113,13 +115,15 @@ impl Config {
 
     /// Set chat-id based in the bot chats,
     /// Send get to `https://api.telegram.org/bot<BOT-TOKEN>/getUpdates`
-    /// to get bot chats.
+    /// to get bot chats, the chats that were fetched by `mcqp results` 
+    /// are read from the saved updates.
     pub async fn set_chat_id(&mut self) {
         let logger = Log::new("set-chat-id");
         let client = Client::new();
         if let Ok(res) = client.get(
             format!("https://api.telegram.org/bot{}/getUpdates", self.bot_token)
         )
+        .query(&[("allowed_updates", json!(telegram::ALLOWED_UPDATES).to_string())])
         .send()
         .await {
             if res.status().is_success() {
@@ -127,13 +131,21 @@ impl Config {
                 if let Ok(info) = res_json {
                     // show all messages sended to the bot
                     println!("\n----- Chats:");
-                    for i in &info.result {
-                        Display::bot_chat_info(&i.message);
-                        println!("");
+                    let saved_messages = results::saved_messages()
+                        .into_iter()
+                        .filter_map( |message| serde_json::from_value::<Message>(message).ok() )
+                        .collect::<Vec<Message>>();
+                    let messages = saved_messages
+                        .iter()
+                        .chain(info.result.iter().filter_map( |update| update.message.as_ref() ))
+                        .collect::<Vec<&Message>>();
+                    for message in &messages {
+                        Display::bot_chat_info(message);
+                        println!();
                     }
                     // Get the chat-id based on the bot chats
                     let chat_id = utils::input("Based on the chats enter your chat-id: ");
-                    if info.result.iter().find(|x| x.message.chat.id.to_string() == chat_id).is_none() {
+                    if messages.iter().find(|x| x.chat.id.to_string() == chat_id).is_none() {
                         logger.error("Chat id is not in the bot chats!");
                     }
                     self.chat_id = chat_id;
@@ -195,7 +207,7 @@ impl Config {
         if !config_file.exists() {
             let config_file_parent = config_file.parent().unwrap();
             if !config_file_parent.exists() {
-                std::fs::create_dir_all(&config_file_parent)
+                std::fs::create_dir_all(config_file_parent)
                     .unwrap_or_else(|_| logger.error("Can NOT create the config dir!"));
             }
         }
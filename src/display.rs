@@ -4,6 +4,8 @@
 use colored::Colorize;
 
 use crate::config::{BotResult, Message};
+use crate::ledger::{Record, Run};
+use crate::utils;
 
 pub struct Display;
 impl Display {
@@ -28,6 +30,31 @@ impl Display {
             message.chat._type.green()
         )
     }
+
+    /// Display the send run information.
+    pub fn run_info(run: &Run) {
+        println!(
+            "Run: {}\nSent At: {}\nChat ID: {}\nFile: {}\nSections: {}",
+            format!("#{}", run.id).green(),
+            utils::format_time(run.sent_at).green(),
+            run.chat_id.green(),
+            run.file.green(),
+            run.records.len().to_string().green()
+        );
+    }
+
+    /// Display the sent section record.
+    pub fn record_info(record: &Record) {
+        println!(
+            "  {} {} {} -> message {} in chat {} at {}",
+            format!("#{}", record.section).cyan(),
+            record.kind.cyan(),
+            record.id.clone().unwrap_or(record.hash.clone()),
+            record.message_id.to_string().green(),
+            record.chat_id.to_string().green(),
+            utils::format_time(record.sent_at).green()
+        );
+    }
 }
 
 pub struct DisplaySyntaxError;
@@ -49,7 +76,7 @@ impl DisplaySyntaxError {
     pub fn error(
         msg: &str, 
         position_msg: &str, 
-        file_path: &std::path::PathBuf, 
+        file_path: &std::path::Path, 
         line: &str, 
         line_number: usize, 
         position: usize
@@ -86,7 +113,7 @@ impl DisplaySyntaxError {
             "|".cyan(),
             position_error_msg.red()
         );
-        println!("");
+        println!();
     }
 
     /// Display the error fix using the add-on.
@@ -134,6 +161,57 @@ impl DisplaySyntaxError {
             "|".cyan(),
             plus_with_position.green()
         );
-        println!("");
+        println!();
+    }
+
+    /// Display the error fix by replacing the char at the position.
+    /// 
+    /// ## Example:
+    /// ```
+    /// DisplaySyntaxError::fix_replace(
+    ///     "Replace the char with `&lt;` to use it.",
+    ///     "1 < 2",
+    ///     "&lt;",
+    ///     3,
+    ///     2
+    /// ); // it will make the line like this "1 &lt; 2"
+    /// ```
+    pub fn fix_replace(
+        msg: &str,
+        line: &str,
+        replace: &str,
+        line_number: usize,
+        position: usize
+    ) {
+        let line_number_width = line_number.to_string().len();
+        let tilde_with_position = format!("{}{}", " ".repeat(position),"~".repeat(replace.chars().count()));
+        let new_line = line
+            .chars()
+            .enumerate()
+            .map( |(index, char)| if index == position { replace.to_string() } else { char.to_string() } )
+            .collect::<String>();
+        println!(
+            "{}: {}",
+            "FIX".green(),
+            msg
+        );
+        println!(
+            " {:line_number_width$} {}", 
+            "",
+            "|".cyan()
+        );
+        println!(
+            " {:>line_number_width$} {} {}",
+            line_number.to_string().cyan(), 
+            "|".cyan(),
+            new_line
+        );
+        println!(
+            " {:line_number_width$} {} {}", 
+            "",
+            "|".cyan(),
+            tilde_with_position.green()
+        );
+        println!();
     }
 }
\ No newline at end of file
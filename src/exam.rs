@@ -0,0 +1,190 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use std::io::Read;
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::file;
+use crate::filter::Filter;
+use crate::ledger::Ledger;
+use crate::limits;
+use crate::log::Log;
+use crate::markup::ParseMode;
+use crate::parser::{self, McqpType};
+use crate::send::{self, SendOptions};
+use crate::telegram::{SentMessage, Telegram};
+use crate::utils;
+
+/// The exam session state, it is saved in the data dir after every 
+/// sent section, so the exam can be resumed after a crash.
+#[derive(Serialize, Deserialize)]
+struct ExamState {
+    /// The content hash of the exam sections.
+    hash: String,
+    /// The ledger run of the exam.
+    run_id: u64,
+    /// The index of the next section.
+    next: usize,
+    /// The time that the last sent poll/question will be closed (unix time).
+    closes_at: u64
+}
+
+impl ExamState {
+    /// Read the exam state, it returns `None` if there is no saved state.
+    fn load(path: &std::path::PathBuf) -> Option<Self> {
+        let mut file = std::fs::File::open(path).ok()?;
+        let mut state_buf = String::new();
+        file.read_to_string(&mut state_buf).ok()?;
+        return serde_json::from_str(&state_buf).ok();
+    }
+
+    /// Save the exam state.
+    fn save(&self, path: &std::path::Path) {
+        let logger = Log::new("exam");
+        let json = serde_json::to_string(&self)
+            .unwrap_or_else(|_| logger.error("Can NOT make json object!"));
+        utils::write_file(path, &json)
+            .unwrap_or_else(|_| logger.error("Can NOT write to the exam state file!"));
+    }
+}
+
+/// Wait until the unix time.
+async fn wait_until(time: u64) {
+    let now = utils::now();
+    if time > now {
+        tokio::time::sleep(std::time::Duration::from_secs(time - now)).await;
+    }
+}
+
+/// Make the answer key messages of the exam questions, the long answer key 
+/// is split between the questions into several messages.
+fn answer_key(abstraction_tree: &parser::McqpAST) -> Vec<String> {
+    let mut message = String::from("*Answer Key*\n");
+    abstraction_tree.mcqps
+        .iter()
+        .filter_map( |section| section.question.as_ref() )
+        .for_each( |question| {
+            let answer = question.answer() as usize;
+            message += &format!(
+                "\n{}\n✅ {}. {}\n", 
+                ParseMode::Markdown.escape(&question.plain_question()),
+                (b'A' + answer as u8) as char,
+                ParseMode::Markdown.escape(&question.plain_choices()[answer])
+            );
+            if let Some(note) = question.plain_note() {
+                message += &format!("💡 {}\n", ParseMode::Markdown.escape(&note));
+            }
+        });
+    return limits::split(&message, ParseMode::Markdown, &limits::MESSAGE).unwrap_or(vec![message]);
+}
+
+/// Send the exam sections one at a time, every poll/question will be open 
+/// for the interval and the next section will be sent after it is closed.
+pub async fn main(command: &ArgMatches) {
+    let logger = Log::new("exam");
+    let file = command.get_one::<String>("FILE").unwrap();
+    let interval = command
+        .get_one::<String>("interval")
+        .map( |interval| {
+            utils::parse_duration(interval)
+                .unwrap_or_else(|| logger.error("Invalid `--interval` duration, expected a duration like `45s`!"))
+                .as_secs()
+        })
+        .unwrap();
+    if !(5..=600).contains(&interval) {
+        logger.error("The interval must be between 5 seconds and 10 minutes!");
+    }
+    let mut abstraction_tree = parser::parse_file(file, &logger);
+    Filter::new(command).apply(&mut abstraction_tree);
+    let mut send_config = config::Config::new();
+    send_config.get_config();
+    let telegram = Telegram::new(&send_config.bot_token);
+    let mut ledger = Ledger::load();
+    let file_path = file::absolute(file);
+    let state_path = utils::data_path(
+        &format!("exams/{}.json", utils::hash(&format!("{}\n{}", file_path, send_config.chat_id)))
+    );
+    let hash = abstraction_tree.hash();
+    let saved_state = if command.get_flag("restart") { None } else { ExamState::load(&state_path) };
+    let mut state = match saved_state {
+        Some(state) if state.hash == hash => {
+            if state.next < abstraction_tree.mcqps.len() {
+                logger.info(
+                    &format!("Resume the exam from section {} of {}.", state.next + 1, abstraction_tree.mcqps.len())
+                );
+            } else {
+                logger.info("Resume the exam, all sections were sent.");
+            }
+            state
+        },
+        saved_state => {
+            if saved_state.is_some() {
+                logger.warn("The exam file was changed, the exam will start from the beginning.");
+            }
+            ExamState {
+                run_id: ledger.new_run(&file_path, &send_config.chat_id, Some(hash.clone())),
+                hash,
+                next: 0,
+                closes_at: 0
+            }
+        }
+    };
+    // The run of the old exam state may NOT be saved in the ledger.
+    if !ledger.has_run(state.run_id) {
+        logger.warn(&format!("The run #{} of the exam is NOT found, the exam will be recorded as a new run.", state.run_id));
+        state.run_id = ledger.new_run(&file_path, &send_config.chat_id, Some(state.hash.clone()));
+    }
+    state.save(&state_path);
+    // Wait for the poll/question that was open when the exam stopped.
+    wait_until(state.closes_at).await;
+    let sections_count = abstraction_tree.mcqps.len();
+    let mut options = SendOptions::default();
+    for (index, section) in abstraction_tree.mcqps.iter().enumerate().skip(state.next) {
+        if let Some(directive) = &section.directive {
+            options.apply(directive).await;
+            state.next = index + 1;
+            state.save(&state_path);
+            continue;
+        }
+        let is_poll = matches!(section._type, McqpType::Poll | McqpType::MCPoll | McqpType::Question);
+        options.open_period = if is_poll { Some(interval) } else { None };
+        match send::send_section(&telegram, &send_config.chat_id, section, &options).await {
+            Ok(sent) => {
+                options.pin_if_needed(&telegram, &sent).await;
+                options = SendOptions { previous: Some(sent.message_id), ..Default::default() };
+                ledger.record(state.run_id, send::record(section, sent));
+                state.next = index + 1;
+                if is_poll {
+                    state.closes_at = utils::now() + interval;
+                }
+                state.save(&state_path);
+                logger.info(
+                    &format!("{} {}/{} sended successfully", section._type.name(), index + 1, sections_count)
+                );
+            },
+            Err(err) => logger.error(
+                &format!("Can NOT send the {}! {}, run the same command to resume the exam.", section._type.name(), err)
+            )
+        }
+        if is_poll {
+            wait_until(state.closes_at).await;
+        }
+    }
+    if command.get_flag("answer-key") && abstraction_tree.question_count > 0 {
+        for part in answer_key(&abstraction_tree) {
+            let answer_key_result = telegram.request::<_, SentMessage>("sendMessage", &serde_json::json!({
+                "chat_id": send_config.chat_id,
+                "text": part,
+                "parse_mode": "Markdown"
+            })).await;
+            if let Err(err) = answer_key_result {
+                logger.error(&format!("Can NOT send the answer key! {}", err));
+            }
+        }
+        logger.info("answer key sended successfully");
+    }
+    let _ = std::fs::remove_file(&state_path);
+    logger.info(&format!("The exam is finished, it is recorded as run #{}.", state.run_id));
+}
@@ -0,0 +1,53 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use crate::parser::McqpAST;
+use super::escape_html;
+
+/// Make the text a valid Anki field, the tabs will break the columns
+/// and the new lines will break the notes.
+fn field(text: &str) -> String {
+    return escape_html(text)
+        .replace('\t', " ")
+        .replace("\r\n", "<br>")
+        .replace('\n', "<br>");
+}
+
+/// Make the text a valid Anki tag, the tags are separated by spaces.
+fn tag(text: &str) -> String {
+    return text.trim().replace(char::is_whitespace, "_");
+}
+
+/// Export the questions to the Anki tab-separated import format,
+/// with the question and the choices in the front and the answer and
+/// the note in the back. The sections tags are used, or the file name
+/// if the section has no tags, and the difficulty is added as `difficulty::<DIFFICULTY>`.
+///
+/// The polls and the messages are skipped, they have no answers.
+pub fn export(abstraction_tree: &McqpAST, file_name: &str) -> String {
+    let mut output = String::from("#separator:tab\n#html:true\n#tags column:3\n");
+    abstraction_tree.mcqps
+        .iter()
+        .filter_map( |section| section.question.as_ref().map( |question| (section, question) ) )
+        .for_each( |(section, question)| {
+            let choices = question.plain_choices();
+            let answer = question.answer() as usize;
+            let mut front = field(&question.plain_question());
+            choices.iter().enumerate().for_each( |(index, choice)| {
+                front += &format!("<br>{}. {}", (b'A' + index as u8) as char, field(choice));
+            });
+            let mut back = format!("{}. {}", (b'A' + answer as u8) as char, field(&choices[answer]));
+            if let Some(note) = question.plain_note() {
+                back += &format!("<br><br>{}", field(&note));
+            }
+            let mut tags = section.meta.tags.iter().map( |section_tag| tag(section_tag) ).collect::<Vec<String>>();
+            if tags.is_empty() {
+                tags.push(tag(file_name));
+            }
+            if let Some(difficulty) = &section.meta.difficulty {
+                tags.push(format!("difficulty::{}", tag(difficulty)));
+            }
+            output += &format!("{}\t{}\t{}\n", front, back, tags.join(" "));
+        });
+    return output;
+}
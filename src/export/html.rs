@@ -0,0 +1,77 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use super::escape_html;
+use super::paper::{self, ExamItem, ExamQuestion, Span};
+
+const STYLE: &str = "\
+body { font-family: serif; max-width: 50em; margin: 2em auto; }
+.columns { column-count: 2; column-gap: 3em; }
+.text { white-space: pre-wrap; margin: 1em 0; }
+.question { break-inside: avoid; margin: 1em 0; }
+.question ol { list-style-type: upper-alpha; margin: 0.3em 0; }
+.answer-key { break-before: page; }
+";
+
+/// Render the Telegram markdown spans to HTML.
+fn spans(spans: &[Span]) -> String {
+    return spans
+        .iter()
+        .map( |span| match span {
+            Span::Text(text) => escape_html(text),
+            Span::Bold(text) => format!("<b>{}</b>", escape_html(text)),
+            Span::Italic(text) => format!("<i>{}</i>", escape_html(text)),
+            Span::Code(text) => format!("<code>{}</code>", escape_html(text)),
+            Span::Pre(text) => format!("<pre>{}</pre>", escape_html(text.trim_matches('\n'))),
+            Span::Link(text, url) => format!("<a href=\"{}\">{}</a>", escape_html(url), escape_html(text))
+        })
+        .collect();
+}
+
+/// Render the question with its lettered choices.
+fn question(question: &ExamQuestion) -> String {
+    let mut html = format!(
+        "<div class=\"question\">\n<p><b>{}.</b> {}{}</p>\n<ol>\n",
+        question.number,
+        escape_html(&question.question),
+        if question.is_multiple { " <i>(choose all that apply)</i>" } else { "" }
+    );
+    question.choices.iter().for_each( |choice| html += &format!("<li>{}</li>\n", escape_html(choice)) );
+    html += "</ol>\n</div>\n";
+    return html;
+}
+
+/// Render the printable exam and its answer key as an HTML page.
+pub fn export(items: &[ExamItem], title: &str, is_two_columns: bool) -> String {
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n<style>\n{1}</style>\n</head>\n<body>\n<h1>{0}</h1>\n",
+        escape_html(title),
+        STYLE
+    );
+    html += if is_two_columns { "<div class=\"columns\">\n" } else { "<div>\n" };
+    items.iter().for_each( |item| match item {
+        ExamItem::Text(text) => html += &format!("<div class=\"text\">{}</div>\n", spans(text)),
+        ExamItem::Question(exam_question) => html += &question(exam_question)
+    });
+    html += "</div>\n";
+    let answered = paper::answered(items);
+    if !answered.is_empty() {
+        html += "<div class=\"answer-key\">\n<h2>Answer Key</h2>\n";
+        answered.iter().for_each( |question| {
+            let answer = question.answer.unwrap();
+            html += &format!(
+                "<p><b>{}.</b> {}. {}",
+                question.number,
+                ExamQuestion::letter(answer),
+                escape_html(&question.choices[answer])
+            );
+            if let Some(note) = &question.note {
+                html += &format!("<br>\n<i>{}</i>", escape_html(note));
+            }
+            html += "</p>\n";
+        });
+        html += "</div>\n";
+    }
+    html += "</body>\n</html>\n";
+    return html;
+}
@@ -0,0 +1,90 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use super::paper::{self, ExamItem, ExamQuestion, Span};
+
+/// Escape the LaTeX special characters of the text.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    text.chars().for_each( |char| match char {
+        '\\' => escaped += "\\textbackslash{}",
+        '~' => escaped += "\\textasciitilde{}",
+        '^' => escaped += "\\textasciicircum{}",
+        '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+            escaped.push('\\');
+            escaped.push(char);
+        },
+        '\n' => escaped += "\\\\\n",
+        _ => escaped.push(char)
+    });
+    return escaped;
+}
+
+/// Render the Telegram markdown spans to LaTeX.
+fn spans(spans: &[Span]) -> String {
+    return spans
+        .iter()
+        .enumerate()
+        .map( |(index, span)| match span {
+            // The verbatim block starts in a new line, so the text line break is not needed.
+            Span::Text(text) if matches!(spans.get(index + 1), Some(Span::Pre(_))) => escape(text.trim_end_matches('\n')),
+            Span::Text(text) => escape(text),
+            Span::Bold(text) => format!("\\textbf{{{}}}", escape(text)),
+            Span::Italic(text) => format!("\\textit{{{}}}", escape(text)),
+            Span::Code(text) => format!("\\texttt{{{}}}", escape(text)),
+            Span::Pre(text) => format!("\n\\begin{{verbatim}}\n{}\n\\end{{verbatim}}\n", text.trim_matches('\n')),
+            Span::Link(text, url) => format!("\\href{{{}}}{{{}}}", escape(url), escape(text))
+        })
+        .collect();
+}
+
+/// Render the printable exam and its answer key as a LaTeX document.
+pub fn export(items: &[ExamItem], title: &str, is_two_columns: bool) -> String {
+    let mut latex = String::from(
+        "\\documentclass[11pt]{article}\n\
+        \\usepackage[utf8]{inputenc}\n\
+        \\usepackage[margin=2cm]{geometry}\n\
+        \\usepackage{enumitem}\n\
+        \\usepackage{multicol}\n\
+        \\usepackage{hyperref}\n"
+    );
+    latex += &format!("\\title{{{}}}\n\\date{{}}\n\\begin{{document}}\n\\maketitle\n", escape(title));
+    if is_two_columns {
+        latex += "\\begin{multicols}{2}\n";
+    }
+    items.iter().for_each( |item| match item {
+        ExamItem::Text(text) => latex += &format!("\n\\noindent {}\n", spans(text)),
+        ExamItem::Question(question) => {
+            latex += &format!(
+                "\n\\begin{{minipage}}{{\\linewidth}}\n\\noindent\\textbf{{{}.}} {}{}\n\\begin{{enumerate}}[label=\\Alph*., nosep]\n",
+                question.number,
+                escape(&question.question),
+                if question.is_multiple { " \\textit{(choose all that apply)}" } else { "" }
+            );
+            question.choices.iter().for_each( |choice| latex += &format!("  \\item {}\n", escape(choice)) );
+            latex += "\\end{enumerate}\n\\end{minipage}\n\\medskip\n";
+        }
+    });
+    if is_two_columns {
+        latex += "\\end{multicols}\n";
+    }
+    let answered = paper::answered(items);
+    if !answered.is_empty() {
+        latex += "\n\\newpage\n\\section*{Answer Key}\n";
+        answered.iter().for_each( |question| {
+            let answer = question.answer.unwrap();
+            latex += &format!(
+                "\n\\noindent\\textbf{{{}.}} {}. {}",
+                question.number,
+                ExamQuestion::letter(answer),
+                escape(&question.choices[answer])
+            );
+            if let Some(note) = &question.note {
+                latex += &format!("\\\\\n\\textit{{{}}}", escape(note));
+            }
+            latex += "\n";
+        });
+    }
+    latex += "\\end{document}\n";
+    return latex;
+}
@@ -0,0 +1,70 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use super::paper::{self, ExamItem, ExamQuestion, Span};
+
+/// Escape the markdown special characters of the text.
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    text.chars().for_each( |char| {
+        if "\\`*_[]<>#|".contains(char) {
+            escaped.push('\\');
+        }
+        escaped.push(char);
+    });
+    // Keep the new lines as line breaks.
+    return escaped.replace('\n', "  \n");
+}
+
+/// Render the Telegram markdown spans to markdown.
+fn spans(spans: &[Span]) -> String {
+    return spans
+        .iter()
+        .map( |span| match span {
+            Span::Text(text) => escape(text),
+            Span::Bold(text) => format!("**{}**", escape(text)),
+            Span::Italic(text) => format!("*{}*", escape(text)),
+            Span::Code(text) => format!("`{}`", text),
+            Span::Pre(text) => format!("\n```\n{}\n```\n", text.trim_matches('\n')),
+            Span::Link(text, url) => format!("[{}](<{}>)", escape(text), url)
+        })
+        .collect();
+}
+
+/// Render the printable exam and its answer key as a markdown document.
+pub fn export(items: &[ExamItem], title: &str) -> String {
+    let mut md = format!("# {}\n\n", escape(title));
+    items.iter().for_each( |item| match item {
+        ExamItem::Text(text) => md += &format!("{}\n\n", spans(text)),
+        ExamItem::Question(question) => {
+            md += &format!(
+                "**{}.** {}{}\n\n",
+                question.number,
+                escape(&question.question),
+                if question.is_multiple { " *(choose all that apply)*" } else { "" }
+            );
+            question.choices.iter().enumerate().for_each( |(index, choice)| {
+                md += &format!("- {}. {}\n", ExamQuestion::letter(index), escape(choice));
+            });
+            md += "\n";
+        }
+    });
+    let answered = paper::answered(items);
+    if !answered.is_empty() {
+        md += "---\n\n## Answer Key\n\n";
+        answered.iter().for_each( |question| {
+            let answer = question.answer.unwrap();
+            md += &format!(
+                "**{}.** {}. {}",
+                question.number,
+                ExamQuestion::letter(answer),
+                escape(&question.choices[answer])
+            );
+            if let Some(note) = &question.note {
+                md += &format!("  \n*{}*", escape(note));
+            }
+            md += "\n\n";
+        });
+    }
+    return md;
+}
@@ -0,0 +1,57 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+mod anki;
+mod html;
+mod latex;
+mod md;
+mod paper;
+
+use clap::ArgMatches;
+
+use crate::filter::Filter;
+use crate::log::Log;
+use crate::parser;
+
+/// Escape the HTML special characters of the text.
+fn escape_html(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+/// Export the .mcq file to another format.
+pub fn main(command: &ArgMatches) {
+    let logger = Log::new("export");
+    let file = command.get_one::<String>("FILE").unwrap();
+    let mut abstraction_tree = parser::parse_file(file, &logger);
+    Filter::new(command).apply(&mut abstraction_tree);
+    let file_name = std::path::Path::new(file)
+        .file_stem()
+        .map( |name| name.to_string_lossy().to_string() )
+        .unwrap_or_default();
+    let format = command.get_one::<String>("format").unwrap().as_str();
+    let is_two_columns = command.get_flag("two-columns");
+    if format == "anki" && abstraction_tree.question_count == 0 {
+        logger.error("There are no questions to export in the file!");
+    }
+    if is_two_columns && matches!(format, "anki" | "md") {
+        logger.warn(&format!("The `{}` format has no columns, `--two-columns` is ignored.", format));
+    }
+    let (output, extension) = match format {
+        "anki" => (anki::export(&abstraction_tree, &file_name), "txt"),
+        "html" => (html::export(&paper::items(&abstraction_tree), &file_name, is_two_columns), "html"),
+        "md" => (md::export(&paper::items(&abstraction_tree), &file_name), "md"),
+        "latex" => (latex::export(&paper::items(&abstraction_tree), &file_name, is_two_columns), "tex"),
+        format => logger.error(&format!("The `{}` format is NOT supported!", format))
+    };
+    let output_path = command
+        .get_one::<String>("output")
+        .map( std::path::PathBuf::from )
+        .unwrap_or_else(|| std::path::Path::new(file).with_extension(extension));
+    std::fs::write(&output_path, output)
+        .unwrap_or_else(|_| logger.error("Can NOT write the output file!"));
+    logger.info(&format!("The file exported to {}.", output_path.display()));
+}
@@ -0,0 +1,141 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use crate::markup::{self, EntityKind, Formatted};
+use crate::parser::McqpAST;
+
+/// The part of the message text, the printable formats have no nested entities.
+pub enum Span {
+    Text(String),
+    Bold(String),
+    Italic(String),
+    Code(String),
+    Pre(String),
+    /// The link `(text, url)`.
+    Link(String, String)
+}
+
+/// The printable exam item.
+pub enum ExamItem {
+    /// The message text.
+    Text(Vec<Span>),
+    /// The poll/question.
+    Question(ExamQuestion)
+}
+
+/// The numbered poll/question of the printable exam.
+pub struct ExamQuestion {
+    /// The question number, it is the `counter` number if it is set.
+    pub number: String,
+    pub question: String,
+    pub choices: Vec<String>,
+    /// The correct choice index, the polls have no answer.
+    pub answer: Option<usize>,
+    pub note: Option<String>,
+    /// The user can choose more than one choice.
+    pub is_multiple: bool
+}
+
+impl ExamQuestion {
+    /// Return the choice letter (`A`, `B`, ...).
+    pub fn letter(index: usize) -> char {
+        return (b'A' + index as u8) as char;
+    }
+}
+
+/// Make the spans of the formatted text, only the outer entities are used and the 
+/// entities that can NOT be printed (spoilers, underlines, ...) are kept as text.
+fn spans(formatted: Formatted) -> Vec<Span> {
+    let chars = formatted.text.chars().collect::<Vec<char>>();
+    let text = |from: usize, to: usize| chars[from..to].iter().collect::<String>();
+    let mut spans = Vec::new();
+    let mut index = 0;
+    for entity in formatted.entities {
+        if entity.offset < index { continue; }
+        let end = entity.offset + entity.length;
+        let content = text(entity.offset, end);
+        let span = match entity.kind {
+            EntityKind::Bold => Span::Bold(content),
+            EntityKind::Italic => Span::Italic(content),
+            EntityKind::Code => Span::Code(content),
+            EntityKind::Pre(_) => Span::Pre(content),
+            EntityKind::Link(url) => Span::Link(content, url),
+            _ => continue
+        };
+        if entity.offset > index {
+            spans.push(Span::Text(text(index, entity.offset)));
+        }
+        spans.push(span);
+        index = end;
+    }
+    if index < chars.len() {
+        spans.push(Span::Text(text(index, chars.len())));
+    }
+    return spans;
+}
+
+/// Split the number that was added by the `counter` config from the question.
+fn split_number(question: &str, is_counter: bool, number: usize) -> (String, String) {
+    if is_counter {
+        if let Some((counter, question)) = question.split_once('.') {
+            if !counter.is_empty() && counter.chars().all( |char| char.is_ascii_digit() ) {
+                return (counter.to_owned(), question.trim().to_owned());
+            }
+        }
+    }
+    return (number.to_string(), question.trim().to_owned());
+}
+
+/// Make the printable exam items of the sections, the directives are skipped.
+pub fn items(abstraction_tree: &McqpAST) -> Vec<ExamItem> {
+    let is_counter = abstraction_tree.config.counter.0;
+    let mut number = 0;
+    return abstraction_tree.mcqps
+        .iter()
+        .filter_map( |section| {
+            if let Some(message) = &section.message {
+                return Some(ExamItem::Text(
+                    markup::parse(&message.m, message.parse_mode)
+                        .map( spans )
+                        .unwrap_or_else(|_| vec![Span::Text(message.m.clone())])
+                ));
+            }
+            if let Some(poll) = &section.poll {
+                number += 1;
+                let (number, question) = split_number(&poll.plain_question(), is_counter, number);
+                return Some(ExamItem::Question(ExamQuestion {
+                    number,
+                    question,
+                    choices: poll.plain_choices(),
+                    answer: None,
+                    note: None,
+                    is_multiple: section._type == crate::parser::McqpType::MCPoll
+                }));
+            }
+            if let Some(question_section) = &section.question {
+                number += 1;
+                let (number, question) = split_number(&question_section.plain_question(), is_counter, number);
+                return Some(ExamItem::Question(ExamQuestion {
+                    number,
+                    question,
+                    choices: question_section.plain_choices(),
+                    answer: Some(question_section.answer() as usize),
+                    note: question_section.plain_note(),
+                    is_multiple: false
+                }));
+            }
+            return None;
+        })
+        .collect();
+}
+
+/// Return the questions that have answers, for the answer key.
+pub fn answered(items: &[ExamItem]) -> Vec<&ExamQuestion> {
+    return items
+        .iter()
+        .filter_map( |item| match item {
+            ExamItem::Question(question) if question.answer.is_some() => Some(question),
+            _ => None
+        })
+        .collect();
+}
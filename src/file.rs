@@ -55,13 +55,22 @@ pub fn state(file: String) -> FileState {
     return FileState::NotFound;
 }
 
+/// Return the absolute path of the file, or the same path if 
+/// it can't be resolved.
+pub fn absolute(file: &str) -> String {
+    return std::fs::canonicalize(file)
+        .map( |path| path.display().to_string() )
+        .unwrap_or(file.to_string());
+}
 
 
 pub struct FileReader {
     /// The file lines.
     lines: Vec<String>,
     /// The number of the current line.
-    current_position: usize
+    current_position: usize,
+    /// The last `next_line` call found the end of the file.
+    is_end: bool
 }
 
 impl FileReader {
@@ -75,7 +84,8 @@ impl FileReader {
             if let Ok(lines) = reader.lines().collect::<Result<Vec<String>,_>>() {
                 return FileReader {
                     lines,
-                    current_position: 0
+                    current_position: 0,
+                    is_end: false
                 }
             } 
             logger.error("Can't read the file!");
@@ -95,16 +105,19 @@ impl FileReader {
     /// ```
     pub fn next_line(&mut self) -> Option<String> {
         if self.current_position < self.lines.len() {
-            let line = self.lines.get(self.current_position).map( |s| s.clone() );
+            let line = self.lines.get(self.current_position).cloned();
             self.current_position += 1;
+            self.is_end = false;
             return line;
         }
+        self.is_end = true;
         return None;
     }
 
-    /// Move the line pointer to the previous line.
+    /// Move the line pointer to the previous line, it will not move 
+    /// if the last `next_line` call found the end of the file.
     pub fn back_to_previous(&mut self) -> Option<bool> {
-        if self.current_position > 0 && self.is_next_line() {
+        if self.current_position > 0 && !self.is_end {
             self.current_position -= 1;
             return Some(true);
         }
@@ -115,12 +128,4 @@ impl FileReader {
     pub fn get_line_number(&self) -> usize {
         return self.current_position;
     }
-
-    /// Check if there is next line.
-    fn is_next_line(&self) -> bool {
-        if self.lines.len() == self.current_position {
-            return false;
-        }
-        return true;
-    }
 }
\ No newline at end of file
@@ -0,0 +1,174 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+
+use crate::log::Log;
+use crate::parser::{Mcqp, McqpAST};
+
+/// The sections filter, it is used to send/check/export a subset of the file.
+#[derive(Serialize, Deserialize)]
+pub struct Filter {
+    /// The section must have all of these tags.
+    tags: Vec<String>,
+    /// The section difficulty.
+    difficulty: Option<String>,
+    /// The sections numbers range `(from, to)`, the numbers starts from 1.
+    only: Option<(usize, usize)>
+}
+
+impl Filter {
+    /// Create the filter from the `--tag`, `--difficulty` and `--only` arguments.
+    pub fn new(command: &ArgMatches) -> Self {
+        let logger = Log::new("filter");
+        let only = command
+            .get_one::<String>("only")
+            .map( |range| {
+                parse_range(range).unwrap_or_else(|| logger.error(
+                    "Invalid `--only` range, expected a range like `5..20` or a section number like `5`!"
+                ))
+            });
+        return Self {
+            tags: command
+                .get_many::<String>("tag")
+                .map( |tags| tags.cloned().collect() )
+                .unwrap_or_default(),
+            difficulty: command.get_one::<String>("difficulty").cloned(),
+            only
+        };
+    }
+
+    /// Check if there is no filter.
+    pub fn is_empty(&self) -> bool {
+        return self.tags.is_empty() && self.difficulty.is_none() && self.only.is_none();
+    }
+
+    /// Check if the section matches the filter.
+    pub fn is_match(&self, section: &Mcqp) -> bool {
+        if let Some((from, to)) = self.only {
+            if section.number < from || section.number > to {
+                return false;
+            }
+        }
+        if let Some(difficulty) = &self.difficulty {
+            match &section.meta.difficulty {
+                Some(d) if d.to_lowercase() == difficulty.to_lowercase() => {},
+                _ => return false
+            }
+        }
+        return self.tags.iter().all( |tag| section.meta.has_tag(tag) );
+    }
+
+    /// Remove the sections that do not match the filter from the tree.
+    pub fn apply(&self, abstraction_tree: &mut McqpAST) {
+        if self.is_empty() { return; }
+        let logger = Log::new("filter");
+        abstraction_tree.retain( |section| self.is_match(section) );
+        if abstraction_tree.mcqps.is_empty() {
+            logger.error("There is no section matches the filter!");
+        }
+        logger.info(
+            &format!(
+                "selected {}/poll and {}/question and {}/message", 
+                abstraction_tree.poll_count,
+                abstraction_tree.question_count,
+                abstraction_tree.message_count
+            )
+        );
+    }
+}
+
+/// Parse the sections range `5..20` or the section number `5`.
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    if let Some((from, to)) = range.split_once("..") {
+        let from = if from.trim().is_empty() { 1 } else { from.trim().parse::<usize>().ok()? };
+        let to = if to.trim().is_empty() { usize::MAX } else { to.trim().parse::<usize>().ok()? };
+        if from == 0 || from > to {
+            return None;
+        }
+        return Some((from, to));
+    }
+    let number = range.trim().parse::<usize>().ok()?;
+    if number == 0 {
+        return None;
+    }
+    return Some((number, number));
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser;
+
+    /// Parse the example file of the metadata.
+    fn meta_example() -> McqpAST {
+        return parser::try_parse_file("example/meta.mcq", std::path::Path::new("example")).unwrap();
+    }
+
+    #[test]
+    fn parse_ranges() {
+        assert_eq!(parse_range("5..20"), Some((5, 20)));
+        assert_eq!(parse_range("..3"), Some((1, 3)));
+        assert_eq!(parse_range("5.."), Some((5, usize::MAX)));
+        assert_eq!(parse_range(" 7 "), Some((7, 7)));
+        assert_eq!(parse_range("0"), None);
+        assert_eq!(parse_range("0..3"), None);
+        assert_eq!(parse_range("7..3"), None);
+        assert_eq!(parse_range("a..b"), None);
+    }
+
+    #[test]
+    fn match_tags_and_difficulty() {
+        let abstraction_tree = meta_example();
+        let filter = Filter {
+            tags: vec!["ALGEBRA".to_string()],
+            difficulty: Some("Hard".to_string()),
+            only: None
+        };
+        let matched = abstraction_tree.mcqps
+            .iter()
+            .filter( |section| filter.is_match(section) )
+            .map( |section| section.key() )
+            .collect::<Vec<String>>();
+        assert_eq!(matched, vec!["algebra-02"]);
+    }
+
+    #[test]
+    fn match_all_tags() {
+        let abstraction_tree = meta_example();
+        let filter = Filter {
+            tags: vec!["algebra".to_string(), "equations".to_string()],
+            difficulty: None,
+            only: None
+        };
+        let matched = abstraction_tree.mcqps
+            .iter()
+            .filter( |section| filter.is_match(section) )
+            .map( |section| section.key() )
+            .collect::<Vec<String>>();
+        assert_eq!(matched, vec!["algebra-01"]);
+    }
+
+    #[test]
+    fn apply_the_range() {
+        let mut abstraction_tree = meta_example();
+        let filter = Filter { tags: Vec::new(), difficulty: None, only: Some((2, 3)) };
+        filter.apply(&mut abstraction_tree);
+        let numbers = abstraction_tree.mcqps
+            .iter()
+            .map( |section| section.number )
+            .collect::<Vec<usize>>();
+        assert_eq!(numbers, vec![2, 3]);
+        assert_eq!(abstraction_tree.question_count, 1);
+        assert_eq!(abstraction_tree.message_count, 1);
+    }
+
+    #[test]
+    fn empty_filter() {
+        let filter = Filter { tags: Vec::new(), difficulty: None, only: None };
+        assert!(filter.is_empty());
+        assert!(meta_example().mcqps.iter().all( |section| filter.is_match(section) ));
+    }
+}
@@ -0,0 +1,35 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use clap::ArgMatches;
+
+use crate::display::Display;
+use crate::ledger::Ledger;
+use crate::log::Log;
+
+/// Display the sent sections from the ledger, the newest run first.
+pub fn main(command: &ArgMatches) {
+    let logger = Log::new("history");
+    let ledger = Ledger::load();
+    let run_id = command.get_one::<String>("run").map( |id| {
+        id.parse::<u64>().unwrap_or_else(|_| logger.error("The run id must be a number!"))
+    });
+    let chat_id = command.get_one::<String>("chat");
+    let runs = ledger.runs
+        .iter()
+        .rev()
+        .filter( |run| run_id.is_none() || Some(run.id) == run_id )
+        .filter( |run| chat_id.is_none() || Some(&run.chat_id) == chat_id )
+        .collect::<Vec<_>>();
+    if runs.is_empty() {
+        logger.info("There is no sent sections.");
+        return;
+    }
+    for run in runs {
+        println!();
+        Display::run_info(run);
+        if run_id.is_some() || command.get_flag("verbose") {
+            run.records.iter().for_each( Display::record_info );
+        }
+    }
+}
@@ -0,0 +1,234 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use std::io::Read;
+use serde::{Deserialize, Serialize};
+
+use crate::log::Log;
+use crate::utils;
+
+/// The ledger file name in the data dir.
+const LEDGER_FILE: &str = "ledger.json";
+
+/// The sent sections ledger, it is saved in the data dir.
+#[derive(Serialize, Deserialize)]
+pub struct Ledger {
+    /// The send runs, the last run is the newest.
+    pub runs: Vec<Run>
+}
+
+/// The send run, all sections sent by one `mcqp send`.
+#[derive(Serialize, Deserialize)]
+pub struct Run {
+    /// The run id.
+    pub id: u64,
+    /// The .mcq file path.
+    pub file: String,
+    /// The configured chat id.
+    pub chat_id: String,
+    /// The run start time (unix time).
+    pub sent_at: u64,
+    /// The content hash of all sent sections.
+    #[serde(default)]
+    pub hash: Option<String>,
+    /// The sent sections.
+    pub records: Vec<Record>
+}
+
+/// The sent section record.
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    /// The section number in the file.
+    pub section: usize,
+    /// The section id from the metadata.
+    pub id: Option<String>,
+    /// The section content hash.
+    pub hash: String,
+    /// The section type name.
+    pub kind: String,
+    /// The telegram message id.
+    pub message_id: i64,
+    /// The telegram chat id.
+    pub chat_id: i64,
+    /// The telegram poll id if the section is a poll or a question.
+    pub poll_id: Option<String>,
+    /// The send time (unix time).
+    pub sent_at: u64,
+    /// The message was deleted from the chat.
+    #[serde(default)]
+    pub deleted: bool,
+    /// The question text if the section is a question.
+    #[serde(default)]
+    pub question: Option<String>,
+    /// The correct option index if the section is a question.
+    #[serde(default)]
+    pub answer: Option<i8>,
+    /// The message ids of the previous messages of the section (the parts 
+    /// of the split message or the overflow message).
+    #[serde(default)]
+    pub parts: Vec<i64>,
+    /// The message flags (`nopreview`, `silent`, `protect` and `reply_to: previous`).
+    #[serde(default)]
+    pub flags: Vec<String>
+}
+
+impl Record {
+    /// Return the section stable key, it is the section id or 
+    /// the section number (`#<number>`) if there is no id.
+    pub fn key(&self) -> String {
+        return self.id
+            .clone()
+            .unwrap_or_else(|| format!("#{}", self.section));
+    }
+}
+
+impl Ledger {
+    /// Read the ledger from the data dir, it will return an empty 
+    /// ledger if there is no ledger file.
+    pub fn load() -> Self {
+        let logger = Log::new("ledger");
+        let ledger_file = utils::data_path(LEDGER_FILE);
+        if !ledger_file.exists() {
+            return Self { runs: Vec::new() };
+        }
+        let mut file = std::fs::File::open(ledger_file)
+            .unwrap_or_else(|_| logger.error("Can NOT open the ledger file!"));
+        let mut ledger_buf = String::new();
+        file
+            .read_to_string(&mut ledger_buf)
+            .unwrap_or_else(|_| logger.error("Can NOT read the ledger file!"));
+        return serde_json::from_str(&ledger_buf)
+            .unwrap_or_else(|_| logger.error("Can NOT parse the ledger!"));
+    }
+
+    /// Merge the saved ledger into this ledger, so the runs and the records that 
+    /// other commands saved after this ledger was read are kept. The records 
+    /// of this ledger win, but a record deleted by another command stays deleted.
+    fn merge(&mut self) {
+        let saved = Self::load();
+        for saved_run in saved.runs {
+            let Some(run) = self.runs.iter_mut().find( |run| run.id == saved_run.id ) else {
+                self.runs.push(saved_run);
+                continue;
+            };
+            for saved_record in saved_run.records {
+                match run.records.iter_mut().find( |record| {
+                    record.chat_id == saved_record.chat_id && record.message_id == saved_record.message_id
+                }) {
+                    Some(record) => record.deleted |= saved_record.deleted,
+                    None => run.records.push(saved_record)
+                }
+            }
+        }
+        self.runs.sort_by_key( |run| run.id );
+    }
+
+    /// Save the ledger to the data dir, the saved ledger is merged first.
+    pub fn save(&mut self) {
+        let logger = Log::new("ledger");
+        self.merge();
+        let json = serde_json::to_string(&self)
+            .unwrap_or_else(|_| logger.error("Can NOT make json object!"));
+        utils::write_file(&utils::data_path(LEDGER_FILE), &json)
+            .unwrap_or_else(|_| logger.error("Can NOT write to the ledger file!"));
+    }
+
+    /// Start a new run, save the ledger and return its id. The ledger is saved 
+    /// here so the run id is NOT given to another command before its first record.
+    pub fn new_run(&mut self, file: &str, chat_id: &str, hash: Option<String>) -> u64 {
+        // The runs of the other commands are merged first, so their ids are NOT reused.
+        self.merge();
+        let id = self.runs.last().map( |run| run.id + 1 ).unwrap_or(1);
+        self.runs.push(Run {
+            id,
+            file: file.to_string(),
+            chat_id: chat_id.to_string(),
+            sent_at: utils::now(),
+            hash,
+            records: Vec::new()
+        });
+        self.save();
+        return id;
+    }
+
+    /// Return the last sent record of every section of the file in the chat, 
+    /// the deleted records are skipped.
+    pub fn live_records(&self, file: &str, chat_id: &str) -> Vec<&Record> {
+        let mut records: Vec<&Record> = Vec::new();
+        self.runs
+            .iter()
+            .filter( |run| run.file == file && run.chat_id == chat_id )
+            .flat_map( |run| run.records.iter() )
+            .for_each( |record| {
+                records.retain( |r| r.key() != record.key() );
+                if !record.deleted {
+                    records.push(record);
+                }
+            });
+        return records;
+    }
+
+    /// Return the last run that sent the same content to the chat, 
+    /// the runs that all of their sections are deleted are skipped.
+    pub fn find_run(&self, hash: &str, chat_id: &str) -> Option<&Run> {
+        return self.runs
+            .iter()
+            .rev()
+            .filter( |run| run.chat_id == chat_id && run.hash.as_deref() == Some(hash) )
+            .find( |run| run.records.iter().any( |record| !record.deleted ) );
+    }
+
+    /// Check if the section content was sent to the chat and not deleted.
+    pub fn is_sent(&self, hash: &str, chat_id: &str) -> bool {
+        return self.runs
+            .iter()
+            .filter( |run| run.chat_id == chat_id )
+            .flat_map( |run| run.records.iter() )
+            .any( |record| record.hash == hash && !record.deleted );
+    }
+
+    /// Mark the message as deleted in all records.
+    pub fn mark_deleted(&mut self, chat_id: i64, message_id: i64) {
+        self.runs
+            .iter_mut()
+            .flat_map( |run| run.records.iter_mut() )
+            .filter( |record| record.chat_id == chat_id && record.message_id == message_id )
+            .for_each( |record| record.deleted = true );
+        self.save();
+    }
+
+    /// Update the hash and the flags of the edited message records, the edited 
+    /// message keeps its id, so it stays in the run that sent it.
+    pub fn mark_edited(&mut self, edited: Record) {
+        self.runs
+            .iter_mut()
+            .flat_map( |run| run.records.iter_mut() )
+            .filter( |record| record.chat_id == edited.chat_id && record.message_id == edited.message_id && !record.deleted )
+            .for_each( |record| {
+                record.hash = edited.hash.clone();
+                record.flags = edited.flags.clone();
+            });
+        self.save();
+    }
+
+    /// Add the record to the run and save the ledger, so the sent 
+    /// sections are recorded even if the next section fails.
+    pub fn record(&mut self, run_id: u64, record: Record) {
+        let Some(run) = self.runs.iter_mut().find( |run| run.id == run_id ) else {
+            Log::new("ledger").warn(&format!(
+                "The run #{} is NOT found, the sent {} (message {}) is NOT recorded!", 
+                run_id, 
+                record.kind, 
+                record.message_id
+            ));
+            return;
+        };
+        run.records.push(record);
+        self.save();
+    }
+
+    /// Check if the run is in the ledger.
+    pub fn has_run(&self, run_id: u64) -> bool {
+        return self.runs.iter().any( |run| run.id == run_id );
+    }
+}
@@ -0,0 +1,137 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use crate::markup::{self, ParseMode};
+
+/// The Telegram length limit of a text, the length is measured by `limits::len`.
+pub struct Limit {
+    pub min: usize,
+    pub max: usize
+}
+
+impl Limit {
+    /// Check if the length is in the limit.
+    pub fn is_valid(&self, len: usize) -> bool {
+        return len >= self.min && len <= self.max;
+    }
+}
+
+/// The poll/question question.
+pub const QUESTION: Limit = Limit { min: 1, max: 255 };
+/// The poll/question option.
+pub const OPTION: Limit = Limit { min: 1, max: 100 };
+/// The question note (the quiz explanation).
+pub const NOTE: Limit = Limit { min: 1, max: 200 };
+/// The message text.
+pub const MESSAGE: Limit = Limit { min: 1, max: 4096 };
+/// The media caption.
+pub const CAPTION: Limit = Limit { min: 1, max: 1024 };
+/// The message button text.
+pub const BUTTON_TEXT: Limit = Limit { min: 1, max: 64 };
+
+/// The max number of the buttons in one row.
+pub const ROW_BUTTONS: usize = 8;
+/// The max number of the buttons in one message.
+pub const BUTTONS: usize = 100;
+
+/// The max size of the uploaded photo (10 MB).
+pub const PHOTO_SIZE: u64 = 10 * 1024 * 1024;
+/// The max size of the uploaded document (50 MB).
+pub const DOCUMENT_SIZE: u64 = 50 * 1024 * 1024;
+
+/// Return the text length as Telegram measures it, Telegram parses the entities
+/// first and then counts the UTF-16 code units of the plain text. So the markup
+/// chars are not counted, and an emoji like `😀` is counted as 2.
+///
+/// ### Example
+/// ```
+/// assert_eq!(limits::len("*hi* 😀", ParseMode::MarkdownV2), 5);
+/// ```
+pub fn len(text: &str, parse_mode: ParseMode) -> usize {
+    return markup::plain(text, parse_mode).encode_utf16().count();
+}
+
+/// Check if the part of the split text can be sent alone.
+fn is_part(part: &str, parse_mode: ParseMode, limit: &Limit) -> bool {
+    return markup::parse(part, parse_mode).is_ok() && limit.is_valid(len(part, parse_mode));
+}
+
+/// Split the long text into parts that are in the limit, the text is split on the
+/// paragraph boundaries first and then on the line boundaries. Every part is checked
+/// by the markup parser, so the entities and the code blocks are NOT cut in half.
+/// It returns `None` if there is no boundary that makes a valid part.
+pub fn split(text: &str, parse_mode: ParseMode, limit: &Limit) -> Option<Vec<String>> {
+    let mut parts = Vec::new();
+    let mut rest = text.trim().to_owned();
+    while len(&rest, parse_mode) > limit.max {
+        let index = ["\n\n", "\n"].iter().find_map( |boundary| {
+            let boundaries = rest
+                .match_indices(boundary)
+                .map( |(index, _)| index )
+                .collect::<Vec<usize>>();
+            // The longest part first.
+            return boundaries.into_iter().rev().find( |&index| {
+                is_part(rest[..index].trim(), parse_mode, limit)
+                    && markup::parse(rest[index..].trim(), parse_mode).is_ok()
+            });
+        })?;
+        parts.push(rest[..index].trim().to_owned());
+        rest = rest[index..].trim().to_owned();
+    }
+    parts.push(rest);
+    return Some(parts);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn len_counts_the_visible_utf16_units() {
+        assert_eq!(len("*hi* 😀", ParseMode::MarkdownV2), 5);
+        assert_eq!(len("<b>bold</b> &amp;", ParseMode::Html), 6);
+        assert_eq!(len("*hi*", ParseMode::None), 4);
+        assert_eq!(len("مرحبا", ParseMode::None), 5);
+    }
+
+    #[test]
+    fn split_on_the_paragraphs_first() {
+        let limit = Limit { min: 1, max: 10 };
+        assert_eq!(
+            split("aaaa\n\nbbbb\n\ncccc", ParseMode::None, &limit),
+            Some(vec!["aaaa\n\nbbbb".to_string(), "cccc".to_string()])
+        );
+    }
+
+    #[test]
+    fn split_on_the_lines() {
+        let limit = Limit { min: 1, max: 5 };
+        assert_eq!(
+            split("aaaa\nbbbb\ncccc", ParseMode::None, &limit),
+            Some(vec!["aaaa".to_string(), "bbbb".to_string(), "cccc".to_string()])
+        );
+    }
+
+    #[test]
+    fn split_keeps_the_short_text() {
+        let limit = Limit { min: 1, max: 10 };
+        assert_eq!(split("  short\n\n", ParseMode::None, &limit), Some(vec!["short".to_string()]));
+    }
+
+    #[test]
+    fn split_does_not_cut_the_entities() {
+        let text = "*aaa\n\nbbb*\n\nccc";
+        assert_eq!(
+            split(text, ParseMode::MarkdownV2, &Limit { min: 1, max: 8 }),
+            Some(vec!["*aaa\n\nbbb*".to_string(), "ccc".to_string()])
+        );
+        // The only boundary that fits the limit is inside the bold entity.
+        assert_eq!(split(text, ParseMode::MarkdownV2, &Limit { min: 1, max: 7 }), None);
+    }
+
+    #[test]
+    fn split_without_boundary() {
+        assert_eq!(split("aaaaaaaaaa", ParseMode::None, &Limit { min: 1, max: 5 }), None);
+    }
+}
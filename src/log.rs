@@ -27,6 +27,16 @@ impl Log {
         );
     }
 
+    /// Print the message as `warning`.
+    pub fn warn(&self, message: &str) {
+        println!(
+            "[{}] @{} - {}",
+            "WARNING".yellow(),
+            self.name.yellow(),
+            message.yellow()
+        );
+    }
+
     /// Print the message as `error` and exit the program.
     pub fn error(&self, message: &str) -> ! {
         println!(
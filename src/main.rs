@@ -15,6 +15,8 @@
 // You should have received a copy of the GNU General Public License
 // along with mcqp.  If not, see <https://www.gnu.org/licenses/>.
 
+// The explicit `return` and the nested `if` are the code style of mcqp.
+#![allow(clippy::needless_return, clippy::collapsible_if)]
 
 use clap::Command;
 
@@ -28,6 +30,22 @@ mod config;
 mod utils;
 mod display;
 mod check;
+mod filter;
+mod telegram;
+mod ledger;
+mod history;
+mod sync;
+mod unsend;
+mod results;
+mod summary;
+mod exam;
+mod schedule;
+mod serve;
+mod practice;
+mod study;
+mod export;
+mod markup;
+mod limits;
 
 #[tokio::main]
 async fn main() {
@@ -0,0 +1,226 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use super::ParseMode;
+
+/// Escape the MarkdownV2 reserved chars of the text.
+fn escape(text: &str) -> String {
+    return ParseMode::MarkdownV2.escape(text);
+}
+
+/// Escape the text inside the code, only the `` ` `` and `\` must be escaped.
+fn escape_code(text: &str) -> String {
+    return text.replace('\\', "\\\\").replace('`', "\\`");
+}
+
+/// Escape the link url, only the `)` and `\` must be escaped.
+fn escape_url(url: &str) -> String {
+    return url.replace('\\', "\\\\").replace(')', "\\)");
+}
+
+/// Find the `pattern` in the chars after the `from` index.
+fn find(chars: &[char], from: usize, pattern: &[char]) -> Option<usize> {
+    return (from..chars.len())
+        .find( |&index| chars[index..].starts_with(pattern) );
+}
+
+/// Find the closing emphasis marker, it must not be after a space, and the
+/// `_` must not be inside a word (`snake_case`).
+fn find_closing(chars: &[char], from: usize, marker: &[char]) -> Option<usize> {
+    let mut index = from;
+    while let Some(end) = find(chars, index, marker) {
+        let is_after_space = end == from || chars[end - 1].is_whitespace();
+        let is_in_word = marker[0] == '_' && chars.get(end + marker.len()).is_some_and( |char| char.is_alphanumeric() );
+        // The longer run (`**` while looking for `*`) is a different marker.
+        let is_longer = chars.get(end + marker.len()) == Some(&marker[0]);
+        if !is_after_space && !is_in_word && !is_longer {
+            return Some(end);
+        }
+        index = end + marker.len();
+    }
+    return None;
+}
+
+/// Find the end of the link destination, the url can have balanced parentheses (`a_(b)`).
+fn find_link_end(chars: &[char], from: usize) -> Option<usize> {
+    let mut depth = 0;
+    for (index, char) in chars.iter().enumerate().skip(from) {
+        match char {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(index),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    return None;
+}
+
+/// Convert the inline CommonMark (emphasis, code, links, ...) to MarkdownV2.
+fn inline(text: &str) -> String {
+    let chars = text.chars().collect::<Vec<char>>();
+    let mut output = String::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let char = chars[index];
+        // The backslash escapes the punctuation chars.
+        if char == '\\' && chars.get(index + 1).is_some_and( |next| next.is_ascii_punctuation() ) {
+            output += &escape(&chars[index + 1].to_string());
+            index += 2;
+            continue;
+        }
+        // The code span, it ends with the same number of backticks.
+        if char == '`' {
+            let run = chars[index..].iter().take_while( |&&next| next == '`' ).count();
+            let marker = vec!['`'; run];
+            if let Some(end) = find(&chars, index + run, &marker) {
+                let code = chars[index + run..end].iter().collect::<String>();
+                output += &format!("`{}`", escape_code(code.trim()));
+                index = end + run;
+                continue;
+            }
+            output += &escape(&marker.iter().collect::<String>());
+            index += run;
+            continue;
+        }
+        // The emphasis, the longer markers first.
+        let emphasis = [
+            ("***", "*_", "_*"),
+            ("___", "*_", "_*"),
+            ("**", "*", "*"),
+            ("__", "*", "*"),
+            ("~~", "~", "~"),
+            ("*", "_", "_"),
+            ("_", "_", "_")
+        ];
+        let emphasis = emphasis.iter().find_map( |(marker, open, close)| {
+            let marker = marker.chars().collect::<Vec<char>>();
+            if !chars[index..].starts_with(&marker) { return None; }
+            let is_before_space = chars.get(index + marker.len()).is_none_or( |next| next.is_whitespace() );
+            let is_in_word = marker[0] == '_' && index > 0 && chars[index - 1].is_alphanumeric();
+            if is_before_space || is_in_word { return None; }
+            return find_closing(&chars, index + marker.len(), &marker)
+                .map( |end| (marker.len(), end, *open, *close) );
+        });
+        if let Some((marker_len, end, open, close)) = emphasis {
+            let content = chars[index + marker_len..end].iter().collect::<String>();
+            output += &format!("{}{}{}", open, inline(&content), close);
+            index = end + marker_len;
+            continue;
+        }
+        // The link `[text](url)` and the image `![alt](url)`, the image is sent as a link.
+        let link_start = if char == '[' { Some(index + 1) } else if chars[index..].starts_with(&['!', '[']) { Some(index + 2) } else { None };
+        if let Some(text_start) = link_start {
+            let link = find(&chars, text_start, &[']', '('])
+                .and_then( |middle| find_link_end(&chars, middle + 2).map( |end| (middle, end) ) );
+            if let Some((middle, end)) = link {
+                let text = chars[text_start..middle].iter().collect::<String>();
+                let destination = chars[middle + 2..end].iter().collect::<String>();
+                // The link title (`[text](url "title")`) is not supported by Telegram.
+                let url = destination.split_whitespace().next().unwrap_or_default().trim_matches(|char| char == '<' || char == '>');
+                output += &format!("[{}]({})", inline(&text), escape_url(url));
+                index = end + 1;
+                continue;
+            }
+        }
+        // The autolink `<https://example.com>`.
+        if char == '<' {
+            if let Some(end) = find(&chars, index + 1, &['>']) {
+                let url = chars[index + 1..end].iter().collect::<String>();
+                if url.contains("://") && !url.contains(char::is_whitespace) {
+                    output += &format!("[{}]({})", escape(&url), escape_url(&url));
+                    index = end + 1;
+                    continue;
+                }
+            }
+        }
+        output += &escape(&char.to_string());
+        index += 1;
+    }
+    return output;
+}
+
+/// Return the list item marker and its content (`- item`, `* item`, `+ item` or `1. item`).
+fn list_item(line: &str) -> Option<(String, &str)> {
+    let trimmed = line.trim_start();
+    let indent = " ".repeat(line.len() - trimmed.len());
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(content) = trimmed.strip_prefix(bullet) {
+            return Some((format!("{}• ", indent), content));
+        }
+    }
+    let digits = trimmed.chars().take_while( |char| char.is_ascii_digit() ).count();
+    if digits > 0 && digits < 10 {
+        let rest = &trimmed[digits..];
+        if let Some(content) = rest.strip_prefix(". ").or(rest.strip_prefix(") ")) {
+            return Some((format!("{}{}\\. ", indent, &trimmed[..digits]), content));
+        }
+    }
+    return None;
+}
+
+/// Check if the line is a thematic break (`---`, `***` or `___`).
+fn is_break(line: &str) -> bool {
+    let line = line.replace(' ', "");
+    return line.len() >= 3 && ["-", "*", "_"].iter().any( |char| line.chars().all( |next| next.to_string() == *char ) );
+}
+
+/// Convert the CommonMark text to MarkdownV2. The Telegram unsupported
+/// blocks have fallbacks: the headings are bold, the lists use `•`, the
+/// thematic breaks are lines and the tables are code blocks.
+///
+/// ### Example
+/// ```
+/// assert_eq!(commonmark::to_markdown_v2("# Title\n**bold** 1.5"), "*Title*\n*bold* 1\\.5");
+/// ```
+pub fn to_markdown_v2(text: &str) -> String {
+    let lines = text.lines().collect::<Vec<&str>>();
+    let mut output = Vec::new();
+    let mut index = 0;
+    while index < lines.len() {
+        let line = lines[index];
+        let trimmed = line.trim_start();
+        // The fenced code block (```` ```rust ```` or `~~~`).
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            let fence = &trimmed[..3];
+            let language = trimmed[3..].trim();
+            let end = (index + 1..lines.len())
+                .find( |&end| lines[end].trim_start().starts_with(fence) )
+                .unwrap_or(lines.len());
+            let code = lines[index + 1..end].join("\n");
+            output.push(format!("```{}\n{}\n```", language, escape_code(&code)));
+            index = end + 1;
+            continue;
+        }
+        // The table, it is a code block to keep its columns.
+        if trimmed.starts_with('|') {
+            let end = (index..lines.len())
+                .find( |&end| !lines[end].trim_start().starts_with('|') )
+                .unwrap_or(lines.len());
+            output.push(format!("```\n{}\n```", escape_code(&lines[index..end].join("\n"))));
+            index = end;
+            continue;
+        }
+        index += 1;
+        // The heading (`# Title`), it is bold.
+        let level = trimmed.chars().take_while( |&char| char == '#' ).count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            let heading = trimmed[level..].trim().trim_end_matches('#').trim_end();
+            output.push(format!("*{}*", inline(heading)));
+            continue;
+        }
+        if is_break(trimmed) {
+            output.push(String::from("——————————"));
+            continue;
+        }
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            output.push(format!(">{}", inline(quote.trim_start())));
+            continue;
+        }
+        if let Some((marker, content)) = list_item(line) {
+            output.push(format!("{}{}", marker, inline(content)));
+            continue;
+        }
+        output.push(inline(line));
+    }
+    return output.join("\n");
+}
@@ -0,0 +1,176 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use super::{Entity, EntityKind, Formatted, MarkupError, MarkupFix};
+
+/// The open tag `(tag name, kind, the tag offset in the text, the entity offset in the plain text)`.
+type OpenTag = (String, Option<EntityKind>, usize, usize);
+
+/// Return the attribute value of the tag (`name="value"`, `name='value'` or `name=value`).
+fn attribute(attributes: &str, name: &str) -> Option<String> {
+    let mut rest = attributes;
+    while let Some(position) = rest.find(name) {
+        let after = rest[position + name.len()..].trim_start();
+        let is_start = position == 0 || rest[..position].ends_with(char::is_whitespace);
+        if is_start && after.starts_with('=') {
+            let value = after[1..].trim_start();
+            return match value.chars().next() {
+                Some(quote) if quote == '"' || quote == '\'' => {
+                    value[1..].find(quote).map( |end| value[1..end + 1].to_owned() )
+                },
+                _ => Some(value.split_whitespace().next().unwrap_or_default().to_owned())
+            };
+        }
+        rest = &rest[position + name.len()..];
+    }
+    return None;
+}
+
+/// Decode the HTML entity (`lt`, `gt`, `amp`, `quot` or a number like `#39` and `#x27`).
+fn decode(entity: &str) -> Option<char> {
+    return match entity {
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "amp" => Some('&'),
+        "quot" => Some('"'),
+        _ => {
+            let number = entity.strip_prefix('#')?;
+            let code = match number.strip_prefix('x').or(number.strip_prefix('X')) {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse::<u32>().ok()?
+            };
+            char::from_u32(code)
+        }
+    };
+}
+
+fn replace_error(offset: usize, message: String, hint: &str, replace: &'static str) -> MarkupError {
+    return MarkupError::new(offset, message, hint, MarkupFix::Replace(replace));
+}
+
+/// Return the entity kind of the open tag, the `code` inside `pre` has no entity.
+fn open_tag(name: &str, attributes: &str, offset: usize, stack: &[OpenTag]) -> Result<Option<EntityKind>, MarkupError> {
+    let tag_error = |message: String, hint: &str| replace_error(offset, message, hint, "&lt;");
+    if let Some((parent, ..)) = stack.last() {
+        let is_code_in_pre = parent == "pre" && name == "code";
+        if (parent == "pre" || parent == "code") && !is_code_in_pre {
+            return Err(tag_error(format!("The <{}> tag can NOT be inside the <{}> tag.", name, parent), "The code can NOT have tags."));
+        }
+    }
+    return Ok(match name {
+        "b" | "strong" => Some(EntityKind::Bold),
+        "i" | "em" => Some(EntityKind::Italic),
+        "u" | "ins" => Some(EntityKind::Underline),
+        "s" | "strike" | "del" => Some(EntityKind::Strikethrough),
+        "tg-spoiler" => Some(EntityKind::Spoiler),
+        "span" => {
+            if attribute(attributes, "class").as_deref() != Some("tg-spoiler") {
+                return Err(tag_error(String::from("The <span> tag must have `class=\"tg-spoiler\"`."), "Only the spoiler span is supported."));
+            }
+            Some(EntityKind::Spoiler)
+        },
+        "a" => {
+            let Some(url) = attribute(attributes, "href") else {
+                return Err(tag_error(String::from("The <a> tag must have the `href` attribute."), "The link has no url."));
+            };
+            Some(EntityKind::Link(url))
+        },
+        "tg-emoji" => {
+            if attribute(attributes, "emoji-id").is_none() {
+                return Err(tag_error(String::from("The <tg-emoji> tag must have the `emoji-id` attribute."), "The emoji has no id."));
+            }
+            Some(EntityKind::CustomEmoji)
+        },
+        "code" if stack.last().is_some_and( |(parent, ..)| parent == "pre" ) => None,
+        "code" => Some(EntityKind::Code),
+        "pre" => Some(EntityKind::Pre(None)),
+        "blockquote" => Some(EntityKind::Blockquote),
+        _ => return Err(tag_error(format!("The <{}> tag is NOT supported.", name), "This must be written as `&lt;`."))
+    });
+}
+
+/// Parse the Telegram HTML text, only the Telegram tags and the
+/// `&lt;`, `&gt;`, `&amp;`, `&quot;` and the numeric entities are supported.
+pub fn parse(text: &str) -> Result<Formatted, MarkupError> {
+    let chars = text.chars().collect::<Vec<char>>();
+    let mut plain = String::new();
+    let mut plain_len = 0;
+    let mut entities = Vec::new();
+    let mut stack: Vec<OpenTag> = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let char = chars[index];
+        match char {
+            '<' => {
+                let Some(end) = (index + 1..chars.len()).find( |&end| chars[end] == '>' ) else {
+                    return Err(replace_error(index, String::from("The char `<` is reserved."), "This must be written as `&lt;`.", "&lt;"));
+                };
+                let tag = chars[index + 1..end].iter().collect::<String>();
+                let is_close = tag.starts_with('/');
+                let tag = tag.trim_start_matches('/');
+                let name_end = tag.find( |char: char| char.is_whitespace() ).unwrap_or(tag.len());
+                let name = tag[..name_end].to_lowercase();
+                if is_close {
+                    match stack.pop() {
+                        Some((open_name, kind, _, offset)) if open_name == name => {
+                            if let Some(kind) = kind {
+                                if plain_len > offset {
+                                    entities.push(Entity { kind, offset, length: plain_len - offset });
+                                }
+                            }
+                        },
+                        Some((open_name, ..)) => {
+                            return Err(replace_error(
+                                index,
+                                format!("The </{}> tag closes the <{}> tag.", name, open_name),
+                                "The inner tag must be closed first.",
+                                "&lt;"
+                            ));
+                        },
+                        None => {
+                            return Err(replace_error(
+                                index,
+                                format!("The </{}> tag has no open tag.", name),
+                                "This must be written as `&lt;`.",
+                                "&lt;"
+                            ));
+                        }
+                    }
+                } else {
+                    let attributes = &tag[name_end..];
+                    let kind = open_tag(&name, attributes, index, &stack)?;
+                    // The language of the code block is the class of its code tag (`<pre><code class="language-rust">`).
+                    if let Some((_, Some(EntityKind::Pre(language)), ..)) = stack.last_mut() {
+                        *language = attribute(attributes, "class")
+                            .and_then( |class| class.strip_prefix("language-").map( |language| language.to_owned() ) );
+                    }
+                    stack.push((name, kind, index, plain_len));
+                }
+                index = end + 1;
+            },
+            '&' => {
+                let entity = (index + 1..chars.len().min(index + 12))
+                    .find( |&end| chars[end] == ';' )
+                    .and_then( |end| decode(&chars[index + 1..end].iter().collect::<String>()).map( |char| (char, end) ) );
+                let Some((decoded, end)) = entity else {
+                    return Err(replace_error(index, String::from("The char `&` is reserved."), "This must be written as `&amp;`.", "&amp;"));
+                };
+                plain.push(decoded);
+                plain_len += 1;
+                index = end + 1;
+            },
+            '>' => {
+                return Err(replace_error(index, String::from("The char `>` is reserved."), "This must be written as `&gt;`.", "&gt;"));
+            },
+            _ => {
+                plain.push(char);
+                plain_len += 1;
+                index += 1;
+            }
+        }
+    }
+    if let Some((name, _, offset, _)) = stack.last() {
+        return Err(replace_error(*offset, format!("The <{}> tag is NOT closed.", name), "This must be closed or written as `&lt;`.", "&lt;"));
+    }
+    return Ok(Formatted::new(plain, entities));
+}
@@ -0,0 +1,84 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use telemark::parser::mdv1;
+
+use super::{Entity, EntityKind, Formatted, MarkupError, MarkupFix};
+
+/// Find the `pattern` in the chars after the `from` index.
+fn find(chars: &[char], from: usize, pattern: &str) -> Option<usize> {
+    let pattern = pattern.chars().collect::<Vec<char>>();
+    return (from..chars.len())
+        .find( |&index| chars[index..].starts_with(&pattern) );
+}
+
+/// Parse the legacy markdown (v1) text, it is validated by `telemark`.
+/// The entities can NOT be nested, and the escaped chars (`\*`) are plain chars.
+pub fn parse(text: &str) -> Result<Formatted, MarkupError> {
+    if let Err(err) = mdv1::parser(text) {
+        return Err(MarkupError::new(
+            err.offset(),
+            String::from("Markdown unclosed."),
+            "This must be escaped or closed.",
+            MarkupFix::Insert("\\")
+        ));
+    }
+    let chars = text.chars().collect::<Vec<char>>();
+    let mut plain = String::new();
+    let mut plain_len = 0;
+    let mut entities = Vec::new();
+    let mut index = 0;
+    while index < chars.len() {
+        let char = chars[index];
+        let entity = match char {
+            '\\' if index + 1 < chars.len() && "_*`[".contains(chars[index + 1]) => {
+                plain.push(chars[index + 1]);
+                plain_len += 1;
+                index += 2;
+                continue;
+            },
+            '`' if chars[index..].starts_with(&['`', '`', '`']) => {
+                find(&chars, index + 3, "```")
+                    .map( |end| (EntityKind::Pre(None), chars[index + 3..end].iter().collect::<String>(), end + 3) )
+            },
+            '`' | '*' | '_' => {
+                find(&chars, index + 1, &char.to_string())
+                    .map( |end| {
+                        let kind = match char {
+                            '`' => EntityKind::Code,
+                            '*' => EntityKind::Bold,
+                            _ => EntityKind::Italic
+                        };
+                        (kind, chars[index + 1..end].iter().collect::<String>(), end + 1)
+                    })
+            },
+            '[' => {
+                find(&chars, index + 1, "](")
+                    .and_then( |middle| find(&chars, middle + 2, ")").map( |end| (middle, end) ) )
+                    .map( |(middle, end)| (
+                        EntityKind::Link(chars[middle + 2..end].iter().collect()),
+                        chars[index + 1..middle].iter().collect::<String>(),
+                        end + 1
+                    ))
+            },
+            _ => None
+        };
+        match entity {
+            Some((kind, content, end)) => {
+                let length = content.chars().count();
+                if length > 0 {
+                    entities.push(Entity { kind, offset: plain_len, length });
+                }
+                plain += &content;
+                plain_len += length;
+                index = end;
+            },
+            None => {
+                plain.push(char);
+                plain_len += 1;
+                index += 1;
+            }
+        }
+    }
+    return Ok(Formatted::new(plain, entities));
+}
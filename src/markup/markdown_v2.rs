@@ -0,0 +1,282 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use super::{Entity, EntityKind, Formatted, MarkupError, MarkupFix};
+
+/// The chars that must be escaped when they are not a part of an entity.
+const RESERVED: &str = "_*[]()~`>#+-=|{}.!";
+
+/// The open entity `(kind, the marker offset in the text, the entity offset in the plain text)`.
+type OpenEntity = (EntityKind, usize, usize);
+
+/// The MarkdownV2 parser state.
+struct Parser {
+    chars: Vec<char>,
+    index: usize,
+    plain: String,
+    plain_len: usize,
+    entities: Vec<Entity>,
+    stack: Vec<OpenEntity>,
+    /// The blockquote offset in the plain text, if the current line is quoted.
+    quote: Option<usize>
+}
+
+impl Parser {
+    fn push(&mut self, char: char) {
+        self.plain.push(char);
+        self.plain_len += 1;
+    }
+
+    fn add_entity(&mut self, kind: EntityKind, offset: usize) {
+        if self.plain_len > offset {
+            self.entities.push(Entity { kind, offset, length: self.plain_len - offset });
+        }
+    }
+
+    fn is_next(&self, pattern: &str) -> bool {
+        let pattern = pattern.chars().collect::<Vec<char>>();
+        return self.chars[self.index..].starts_with(&pattern);
+    }
+
+    fn escape_error(&self, offset: usize, message: String, hint: &str) -> MarkupError {
+        return MarkupError::new(offset, message, hint, MarkupFix::Insert("\\"));
+    }
+
+    /// Open the entity, or close it if it is open.
+    fn toggle(&mut self, kind: EntityKind, marker_len: usize) -> Result<(), MarkupError> {
+        match self.stack.iter().rposition( |(open_kind, _, _)| *open_kind == kind ) {
+            Some(position) if position + 1 == self.stack.len() => {
+                let (kind, _, offset) = self.stack.pop().unwrap();
+                self.add_entity(kind, offset);
+            },
+            Some(_) => {
+                let (inner_kind, _, _) = self.stack.last().unwrap();
+                return Err(self.escape_error(
+                    self.index,
+                    format!("The {} entity is closed before the {} entity inside it.", kind.name(), inner_kind.name()),
+                    "The inner entity must be closed first."
+                ));
+            },
+            None => self.stack.push((kind, self.index, self.plain_len))
+        }
+        self.index += marker_len;
+        return Ok(());
+    }
+
+    /// Parse the inline code or the code block, only the `` ` `` and `\` must be escaped inside them.
+    fn code(&mut self) -> Result<(), MarkupError> {
+        let start = self.index;
+        let marker = if self.is_next("```") { "```" } else { "`" };
+        self.index += marker.len();
+        let mut content = String::new();
+        loop {
+            if self.index >= self.chars.len() {
+                return Err(self.escape_error(
+                    start,
+                    format!("The {} entity is NOT closed.", if marker == "`" { "code" } else { "pre" }),
+                    "This must be escaped or closed."
+                ));
+            }
+            if self.is_next(marker) { break; }
+            let char = self.chars[self.index];
+            if char == '\\' && self.index + 1 < self.chars.len() {
+                content.push(self.chars[self.index + 1]);
+                self.index += 2;
+                continue;
+            }
+            if char == '`' {
+                return Err(self.escape_error(
+                    self.index,
+                    String::from("The `` ` `` must be escaped inside the code."),
+                    "This must be escaped."
+                ));
+            }
+            content.push(char);
+            self.index += 1;
+        }
+        self.index += marker.len();
+        let offset = self.plain_len;
+        let kind = if marker == "`" {
+            EntityKind::Code
+        } else {
+            // The first line is the language if it has no spaces.
+            let mut language = None;
+            if let Some((first_line, code)) = content.split_once('\n') {
+                let first_line = first_line.trim();
+                if !first_line.is_empty() && !first_line.contains(' ') {
+                    language = Some(first_line.to_owned());
+                    content = code.to_owned();
+                }
+            }
+            EntityKind::Pre(language)
+        };
+        content.chars().for_each( |char| self.push(char) );
+        self.add_entity(kind, offset);
+        return Ok(());
+    }
+
+    /// Close the link text (`]`) and parse its url (`(url)`).
+    fn link(&mut self) -> Result<(), MarkupError> {
+        let Some(position) = self.stack.iter().rposition( |(kind, _, _)| matches!(kind, EntityKind::Link(_) | EntityKind::CustomEmoji) ) else {
+            return Err(self.escape_error(self.index, String::from("The char `]` is reserved."), "This must be escaped."));
+        };
+        if position + 1 != self.stack.len() {
+            let (inner_kind, _, _) = self.stack.last().unwrap();
+            return Err(self.escape_error(
+                self.index,
+                format!("The link is closed before the {} entity inside it.", inner_kind.name()),
+                "The inner entity must be closed first."
+            ));
+        }
+        let close = self.index;
+        self.index += 1;
+        if !self.is_next("(") {
+            return Err(self.escape_error(close, String::from("The link has no url."), "Expected `(url)` after this."));
+        }
+        self.index += 1;
+        let mut url = String::new();
+        loop {
+            if self.index >= self.chars.len() {
+                return Err(self.escape_error(close + 1, String::from("The link url is NOT closed."), "This must be closed with `)`."));
+            }
+            let char = self.chars[self.index];
+            self.index += 1;
+            match char {
+                ')' => break,
+                '\\' if self.index < self.chars.len() => {
+                    url.push(self.chars[self.index]);
+                    self.index += 1;
+                },
+                _ => url.push(char)
+            }
+        }
+        let (kind, _, offset) = self.stack.pop().unwrap();
+        let kind = match kind {
+            EntityKind::Link(_) => EntityKind::Link(url),
+            kind => kind
+        };
+        self.add_entity(kind, offset);
+        return Ok(());
+    }
+
+    /// Parse the blockquote marker (`>` or `**>`) at the start of the line.
+    fn line_start(&mut self) {
+        let marker_len = if self.is_next(">") {
+            1
+        } else if self.is_next("**>") {
+            3
+        } else {
+            if let Some(offset) = self.quote.take() {
+                // The quote ends with the previous line, without its new line.
+                let length = self.plain_len - 1 - offset;
+                if length > 0 {
+                    self.entities.push(Entity { kind: EntityKind::Blockquote, offset, length });
+                }
+            }
+            return;
+        };
+        if self.quote.is_none() {
+            self.quote = Some(self.plain_len);
+        }
+        self.index += marker_len;
+    }
+
+    fn parse(&mut self) -> Result<(), MarkupError> {
+        let mut is_line_start = true;
+        while self.index < self.chars.len() {
+            if is_line_start {
+                is_line_start = false;
+                self.line_start();
+                continue;
+            }
+            let char = self.chars[self.index];
+            match char {
+                '\\' => {
+                    match self.chars.get(self.index + 1) {
+                        Some(&next) if (1..=126).contains(&(next as u32)) => {
+                            self.push(next);
+                            self.index += 2;
+                        },
+                        _ => return Err(self.escape_error(
+                            self.index,
+                            String::from("The `\\` escapes nothing."),
+                            "This must be escaped."
+                        ))
+                    }
+                },
+                '`' => self.code()?,
+                '*' => self.toggle(EntityKind::Bold, 1)?,
+                '~' => self.toggle(EntityKind::Strikethrough, 1)?,
+                '_' => {
+                    let is_italic_open = self.stack.last().is_some_and( |(kind, _, _)| *kind == EntityKind::Italic );
+                    if self.is_next("__") && !is_italic_open {
+                        self.toggle(EntityKind::Underline, 2)?;
+                    } else {
+                        self.toggle(EntityKind::Italic, 1)?;
+                    }
+                },
+                '|' if self.is_next("||") => {
+                    let is_spoiler_open = self.stack.iter().any( |(kind, _, _)| *kind == EntityKind::Spoiler );
+                    let is_line_end = matches!(self.chars.get(self.index + 2), None | Some('\n'));
+                    if self.quote.is_some() && !is_spoiler_open && is_line_end {
+                        // The end of the expandable blockquote.
+                        self.index += 2;
+                    } else {
+                        self.toggle(EntityKind::Spoiler, 2)?;
+                    }
+                },
+                '[' => {
+                    self.stack.push((EntityKind::Link(String::new()), self.index, self.plain_len));
+                    self.index += 1;
+                },
+                '!' if self.is_next("![") => {
+                    self.stack.push((EntityKind::CustomEmoji, self.index, self.plain_len));
+                    self.index += 2;
+                },
+                ']' => self.link()?,
+                '\n' => {
+                    self.push(char);
+                    self.index += 1;
+                    is_line_start = true;
+                },
+                _ if RESERVED.contains(char) => {
+                    return Err(self.escape_error(
+                        self.index,
+                        format!("The char `{}` is reserved.", char),
+                        "This must be escaped."
+                    ));
+                },
+                _ => {
+                    self.push(char);
+                    self.index += 1;
+                }
+            }
+        }
+        if let Some((kind, marker, _)) = self.stack.last() {
+            return Err(self.escape_error(
+                *marker,
+                format!("The {} entity is NOT closed.", kind.name()),
+                "This must be escaped or closed."
+            ));
+        }
+        if let Some(offset) = self.quote.take() {
+            self.add_entity(EntityKind::Blockquote, offset);
+        }
+        return Ok(());
+    }
+}
+
+/// Parse the MarkdownV2 text, the entities can be nested.
+pub fn parse(text: &str) -> Result<Formatted, MarkupError> {
+    let mut parser = Parser {
+        chars: text.chars().collect(),
+        index: 0,
+        plain: String::new(),
+        plain_len: 0,
+        entities: Vec::new(),
+        stack: Vec::new(),
+        quote: None
+    };
+    parser.parse()?;
+    return Ok(Formatted::new(parser.plain, parser.entities));
+}
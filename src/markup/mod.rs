@@ -0,0 +1,294 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+pub mod commonmark;
+mod html;
+mod markdown;
+mod markdown_v2;
+
+/// The Telegram parse mode of the text.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ParseMode {
+    /// The legacy markdown (v1), it is the default.
+    Markdown,
+    MarkdownV2,
+    Html,
+    /// The plain text.
+    None
+}
+
+impl ParseMode {
+    /// Return the parse mode by its name in the .mcq file (`markdown`, `markdownv2`, `html` or `none`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        return match name.to_lowercase().as_str() {
+            "markdown" => Some(ParseMode::Markdown),
+            "markdownv2" => Some(ParseMode::MarkdownV2),
+            "html" => Some(ParseMode::Html),
+            "none" => Some(ParseMode::None),
+            _ => None
+        };
+    }
+
+    /// Return the parse mode name in the .mcq file.
+    pub fn name(&self) -> &str {
+        return match self {
+            ParseMode::Markdown => "markdown",
+            ParseMode::MarkdownV2 => "markdownv2",
+            ParseMode::Html => "html",
+            ParseMode::None => "none"
+        };
+    }
+
+    /// Escape the text, so it is sent as it is in the parse mode.
+    ///
+    /// ### Example
+    /// ```
+    /// assert_eq!(ParseMode::MarkdownV2.escape("Q1 (see above)"), "Q1 \\(see above\\)");
+    /// ```
+    pub fn escape(&self, text: &str) -> String {
+        return match self {
+            ParseMode::Markdown => text
+                .chars()
+                .map( |char| if "_*`[".contains(char) { format!("\\{}", char) } else { char.to_string() } )
+                .collect(),
+            ParseMode::MarkdownV2 => text
+                .chars()
+                .map( |char| if "\\_*[]()~`>#+-=|{}.!".contains(char) { format!("\\{}", char) } else { char.to_string() } )
+                .collect(),
+            ParseMode::Html => text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;"),
+            ParseMode::None => text.to_owned()
+        };
+    }
+
+    /// Return the `parse_mode` value of the Telegram API, the plain text has no parse mode.
+    pub fn api_name(&self) -> Option<String> {
+        return match self {
+            ParseMode::Markdown => Some(String::from("Markdown")),
+            ParseMode::MarkdownV2 => Some(String::from("MarkdownV2")),
+            ParseMode::Html => Some(String::from("HTML")),
+            ParseMode::None => None
+        };
+    }
+}
+
+/// The formatting entity type.
+#[derive(Clone, PartialEq, Debug)]
+pub enum EntityKind {
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    Spoiler,
+    Code,
+    /// The code block with its language.
+    Pre(Option<String>),
+    /// The link with its url.
+    Link(String),
+    CustomEmoji,
+    Blockquote
+}
+
+impl EntityKind {
+    /// Return the entity name, it is used in the errors.
+    pub fn name(&self) -> &str {
+        return match self {
+            EntityKind::Bold => "bold",
+            EntityKind::Italic => "italic",
+            EntityKind::Underline => "underline",
+            EntityKind::Strikethrough => "strikethrough",
+            EntityKind::Spoiler => "spoiler",
+            EntityKind::Code => "code",
+            EntityKind::Pre(_) => "pre",
+            EntityKind::Link(_) => "link",
+            EntityKind::CustomEmoji => "custom emoji",
+            EntityKind::Blockquote => "blockquote"
+        };
+    }
+}
+
+/// The formatting entity, the offset and the length are in chars of the plain text.
+#[derive(Clone, Debug)]
+pub struct Entity {
+    pub kind: EntityKind,
+    pub offset: usize,
+    pub length: usize
+}
+
+/// The text after parsing its markup, it is the text that the users see.
+pub struct Formatted {
+    /// The plain text without the markup.
+    pub text: String,
+    /// The entities sorted by the offset.
+    pub entities: Vec<Entity>
+}
+
+impl Formatted {
+    fn new(text: String, mut entities: Vec<Entity>) -> Self {
+        // The outer entity first if they start at the same offset.
+        entities.sort_by( |a, b| a.offset.cmp(&b.offset).then(b.length.cmp(&a.length)) );
+        return Self { text, entities };
+    }
+}
+
+/// The fix of the markup error, it is applied at the error offset.
+#[derive(Clone, Copy, Debug)]
+pub enum MarkupFix {
+    /// Insert the text before the char.
+    Insert(&'static str),
+    /// Replace the char with the text.
+    Replace(&'static str)
+}
+
+/// The markup error, it points to the offending char.
+#[derive(Debug)]
+pub struct MarkupError {
+    /// The offset of the offending char in the text (in chars).
+    pub offset: usize,
+    /// The error message.
+    pub message: String,
+    /// The message under the offending char.
+    pub hint: String,
+    pub fix: MarkupFix
+}
+
+impl MarkupError {
+    fn new(offset: usize, message: String, hint: &str, fix: MarkupFix) -> Self {
+        return Self { offset, message, hint: hint.to_owned(), fix };
+    }
+
+    /// Return the fix message.
+    pub fn fix_message(&self) -> String {
+        return match self.fix {
+            MarkupFix::Insert(_) => String::from("Escape the char to use it."),
+            MarkupFix::Replace(text) => format!("Replace the char with `{}` to use it.", text)
+        };
+    }
+
+    /// Apply the fix to the text.
+    pub fn apply_fix(&self, text: &str) -> String {
+        let mut fixed = String::new();
+        text.chars().enumerate().for_each( |(index, char)| {
+            if index != self.offset {
+                fixed.push(char);
+                return;
+            }
+            match self.fix {
+                MarkupFix::Insert(insert) => {
+                    fixed += insert;
+                    fixed.push(char);
+                },
+                MarkupFix::Replace(replace) => fixed += replace
+            }
+        });
+        return fixed;
+    }
+}
+
+/// Parse the text markup in the parse mode, and return the plain text with its entities.
+///
+/// ### Example
+/// ```
+/// let formatted = markup::parse("*hi* all", ParseMode::MarkdownV2).unwrap();
+/// assert_eq!(formatted.text, "hi all");
+/// assert_eq!(formatted.entities[0].kind, EntityKind::Bold);
+/// ```
+pub fn parse(text: &str, parse_mode: ParseMode) -> Result<Formatted, MarkupError> {
+    return match parse_mode {
+        ParseMode::Markdown => markdown::parse(text),
+        ParseMode::MarkdownV2 => markdown_v2::parse(text),
+        ParseMode::Html => html::parse(text),
+        ParseMode::None => Ok(Formatted::new(text.to_owned(), Vec::new()))
+    };
+}
+
+/// Fix the markup errors of the text by escaping the offending chars, so it can be sent.
+pub fn fix(text: &str, parse_mode: ParseMode) -> String {
+    let mut fixed = text.to_owned();
+    // Every fix makes one more char a plain char, so it ends.
+    for _ in 0..=text.chars().count() {
+        match parse(&fixed, parse_mode) {
+            Ok(_) => break,
+            Err(err) => fixed = err.apply_fix(&fixed)
+        }
+    }
+    return fixed;
+}
+
+/// Return the plain text that the users see, the text with markup errors is returned as it is.
+pub fn plain(text: &str, parse_mode: ParseMode) -> String {
+    return parse(text, parse_mode)
+        .map( |formatted| formatted.text )
+        .unwrap_or_else(|_| text.to_owned());
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_markdown_v2_entities() {
+        let formatted = parse("*bold _italic_* and `code`", ParseMode::MarkdownV2).unwrap();
+        assert_eq!(formatted.text, "bold italic and code");
+        let entities = formatted.entities
+            .iter()
+            .map( |entity| (entity.kind.clone(), entity.offset, entity.length) )
+            .collect::<Vec<(EntityKind, usize, usize)>>();
+        assert_eq!(entities, vec![
+            (EntityKind::Bold, 0, 11),
+            (EntityKind::Italic, 5, 6),
+            (EntityKind::Code, 16, 4)
+        ]);
+    }
+
+    #[test]
+    fn parse_markdown_v2_links_and_escapes() {
+        let formatted = parse("\\*not bold\\* [site](https://mcqp.github.io/)", ParseMode::MarkdownV2).unwrap();
+        assert_eq!(formatted.text, "*not bold* site");
+        assert_eq!(formatted.entities.len(), 1);
+        assert_eq!(formatted.entities[0].kind, EntityKind::Link("https://mcqp.github.io/".to_string()));
+        assert_eq!(formatted.entities[0].offset, 11);
+    }
+
+    #[test]
+    fn parse_markdown_v2_errors() {
+        assert!(parse("*not closed", ParseMode::MarkdownV2).is_err());
+        assert!(parse("1 + 1 = 2.", ParseMode::MarkdownV2).is_err());
+        assert!(parse("1 \\+ 1 \\= 2\\.", ParseMode::MarkdownV2).is_ok());
+    }
+
+    #[test]
+    fn parse_html_entities() {
+        let formatted = parse("<b>bold</b> &lt;tag&gt;", ParseMode::Html).unwrap();
+        assert_eq!(formatted.text, "bold <tag>");
+        assert_eq!(formatted.entities[0].kind, EntityKind::Bold);
+        assert!(parse("<b>not closed", ParseMode::Html).is_err());
+    }
+
+    #[test]
+    fn fix_escapes_the_errors() {
+        for (text, parse_mode) in [
+            ("*not closed", ParseMode::MarkdownV2),
+            ("1 + 1 = 2.", ParseMode::MarkdownV2),
+            ("a < b & c", ParseMode::Html)
+        ] {
+            let fixed = fix(text, parse_mode);
+            assert_eq!(plain(&fixed, parse_mode), text, "{}", fixed);
+        }
+        assert_eq!(fix("a < b", ParseMode::Html), "a &lt; b");
+    }
+
+    #[test]
+    fn fix_keeps_the_valid_text() {
+        assert_eq!(fix("*bold* text", ParseMode::MarkdownV2), "*bold* text");
+        assert_eq!(fix("*any* text", ParseMode::None), "*any* text");
+    }
+
+    #[test]
+    fn escape_is_plain() {
+        let text = "Q1 (see above) *_[]~`>#+-=|{}.! <b> & \\";
+        for parse_mode in [ParseMode::MarkdownV2, ParseMode::Html, ParseMode::None] {
+            assert_eq!(plain(&parse_mode.escape(text), parse_mode), text);
+        }
+    }
+}
@@ -10,7 +10,17 @@ pub async fn main(commands: ArgMatches) {
     match commands.subcommand() {
         Some(("send", command)) => crate::send::main(command).await,
         Some(("config", command)) => crate::config::main(command).await,
+        Some(("exam", command)) => crate::exam::main(command).await,
+        Some(("schedule", command)) => crate::schedule::main(command).await,
+        Some(("serve", command)) => crate::serve::main(command).await,
+        Some(("practice", command)) => crate::practice::main(command),
+        Some(("study", command)) => crate::study::main(command),
+        Some(("export", command)) => crate::export::main(command),
         Some(("check", command)) => crate::check::main(command),
+        Some(("sync", command)) => crate::sync::main(command).await,
+        Some(("unsend", command)) => crate::unsend::main(command).await,
+        Some(("results", command)) => crate::results::main(command).await,
+        Some(("history", command)) => crate::history::main(command),
         _ => logger.error("Please use `--help` from the help message!")
     }
 }
\ No newline at end of file
@@ -0,0 +1,55 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use pest::iterators::Pairs;
+
+use super::Rule;
+use super::media_parser::{Media, MediaKind};
+
+/// The album section, it is sent as one Telegram media group:
+/// ```
+/// album:(
+/// ./a.png | The caption of the first photo
+/// ./b.png
+/// ):enda
+/// ```
+pub struct Album {
+    /// The album photos or documents.
+    pub items: Vec<Media>
+}
+
+impl Album {
+    pub fn new() -> Self {
+        return Self { items: Vec::new() };
+    }
+
+    /// Parse the album item line (`./a.png | The caption`), the path is resolved 
+    /// relative to the `dir` of the .mcq file and the type is found by its extension.
+    pub fn parse_item(item_ast: Pairs<'_, Rule>, dir: &std::path::Path) -> Media {
+        let mut media = Media::new();
+        item_ast
+            .flatten()
+            .for_each( |pair| match pair.as_rule() {
+                Rule::ALBUM_ITEM_PATH => {
+                    media.path = pair.as_str().trim().to_string();
+                    media.file = dir.join(&media.path);
+                    media.kind = MediaKind::from_file(&media.file);
+                },
+                Rule::ALBUM_ITEM_CAPTION => media.caption = Some(pair.as_str().trim().to_string()),
+                _ => {}
+            });
+        return media;
+    }
+
+    /// Check the number of the items, the album has 2 to 10 items.
+    pub fn is_valid(&self) -> bool {
+        return self.items.len() >= 2 && self.items.len() <= 10;
+    }
+
+    /// Check if the items have the same type, Telegram can NOT group the photos with the documents.
+    pub fn is_compatible(&self) -> bool {
+        return self.items
+            .windows(2)
+            .all( |items| items[0].kind == items[1].kind );
+    }
+}
@@ -3,22 +3,48 @@
 
 use pest::iterators::Pairs;
 use super::Rule;
+use crate::markup::ParseMode;
 
 pub struct Config {
     /// The poll/question counter `(is_set: bool, start_from: usize)`
     pub counter: (bool, usize),
     /// The message markdown errors.
-    pub md_err: bool
+    pub md_err: bool,
+    /// The polls/questions are anonymous.
+    pub anonymous: bool,
+    /// The messages parse mode.
+    pub parse_mode: ParseMode,
+    /// The messages are written in CommonMark.
+    pub commonmark: bool,
+    /// The polls/questions parse mode, it is used for the question, options and note.
+    pub poll_parse_mode: ParseMode,
+    /// The long messages are split into several messages.
+    pub split_long_messages: bool,
+    /// The long questions/options are sent in a preceding message.
+    pub overflow: bool
 }
 
 impl Config {
     /// Create new Config.
     /// Setting:
     /// - `counter` to `(false, 0)`
+    /// - `md_err` to `false`
+    /// - `anonymous` to `true`
+    /// - `parse_mode` to `markdown`
+    /// - `markdown` to `telegram`
+    /// - `poll_parse_mode` to `none`
+    /// - `split_long_messages` to `false`
+    /// - `overflow` to `error`
     pub fn new() -> Self {
         return Self {
             counter: (false, 0),
-            md_err: false
+            md_err: false,
+            anonymous: true,
+            parse_mode: ParseMode::Markdown,
+            commonmark: false,
+            poll_parse_mode: ParseMode::None,
+            split_long_messages: false,
+            overflow: false
         };
     }
 
@@ -49,8 +75,84 @@ impl Config {
                                 self.md_err = md_err_pair.as_str().to_lowercase() == "true";
                             });
                     },
+                    Rule::CONFIG_ANONYMOUS => {
+                        inner_pair
+                            .into_inner()
+                            .filter( |anonymous_pair| anonymous_pair.as_rule() == Rule::CONFIG_ANONYMOUS_VALUE)
+                            .take(1)
+                            .for_each( |anonymous_pair| {
+                                self.anonymous = anonymous_pair.as_str().to_lowercase() == "true";
+                            });
+                    },
+                    Rule::CONFIG_PARSE_MODE => {
+                        inner_pair
+                            .into_inner()
+                            .filter( |parse_mode_pair| parse_mode_pair.as_rule() == Rule::PARSE_MODE_VALUE)
+                            .take(1)
+                            .for_each( |parse_mode_pair| {
+                                if let Some(parse_mode) = ParseMode::from_name(parse_mode_pair.as_str()) {
+                                    self.parse_mode = parse_mode;
+                                }
+                            });
+                    },
+                    Rule::CONFIG_MARKDOWN => {
+                        inner_pair
+                            .into_inner()
+                            .filter( |markdown_pair| markdown_pair.as_rule() == Rule::CONFIG_MARKDOWN_VALUE)
+                            .take(1)
+                            .for_each( |markdown_pair| {
+                                self.commonmark = markdown_pair.as_str().to_lowercase() == "commonmark";
+                            });
+                    },
+                    Rule::CONFIG_POLL_PARSE_MODE => {
+                        inner_pair
+                            .into_inner()
+                            .filter( |parse_mode_pair| parse_mode_pair.as_rule() == Rule::PARSE_MODE_VALUE)
+                            .take(1)
+                            .for_each( |parse_mode_pair| {
+                                if let Some(parse_mode) = ParseMode::from_name(parse_mode_pair.as_str()) {
+                                    self.poll_parse_mode = parse_mode;
+                                }
+                            });
+                    },
+                    Rule::CONFIG_SPLIT_LONG_MESSAGES => {
+                        inner_pair
+                            .into_inner()
+                            .filter( |split_pair| split_pair.as_rule() == Rule::CONFIG_SPLIT_LONG_MESSAGES_VALUE)
+                            .take(1)
+                            .for_each( |split_pair| {
+                                self.split_long_messages = split_pair.as_str().to_lowercase() == "true";
+                            });
+                    },
+                    Rule::CONFIG_OVERFLOW => {
+                        inner_pair
+                            .into_inner()
+                            .filter( |overflow_pair| overflow_pair.as_rule() == Rule::CONFIG_OVERFLOW_VALUE)
+                            .take(1)
+                            .for_each( |overflow_pair| {
+                                self.overflow = overflow_pair.as_str().to_lowercase() == "message";
+                            });
+                    },
                     _ => {}
                 }
             });
     }
+    /// Return the settings that are NOT the defaults (e.g. `anonymous = false`), they 
+    /// are added to the file hash, so the same file with another config is sent again.
+    pub fn changed(&self) -> Vec<String> {
+        return [
+            (self.counter.0, "counter".to_string()),
+            (self.md_err, "md_err = true".to_string()),
+            (!self.anonymous, "anonymous = false".to_string()),
+            (self.parse_mode != ParseMode::Markdown, format!("parse_mode = {}", self.parse_mode.name())),
+            (self.commonmark, "markdown = commonmark".to_string()),
+            (self.poll_parse_mode != ParseMode::None, format!("poll_parse_mode = {}", self.poll_parse_mode.name())),
+            (self.split_long_messages, "split_long_messages = true".to_string()),
+            (self.overflow, "overflow = message".to_string())
+        ]
+            .into_iter()
+            .filter( |(is_changed, _)| *is_changed )
+            .map( |(_, setting)| setting )
+            .collect();
+    }
 }
\ No newline at end of file
@@ -0,0 +1,41 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use std::time::Duration;
+use pest::iterators::Pairs;
+use super::Rule;
+use crate::utils;
+
+/// The directive between the sections, the sender executes 
+/// the directives in order.
+#[derive(PartialEq, Debug, Clone)]
+pub enum Directive {
+    /// Wait before sending the next section (`wait: 30s`).
+    Wait(Duration),
+    /// Pin the next section (`pin:`).
+    Pin,
+    /// Send the next section without notification (`silent:`).
+    Silent
+}
+
+impl Directive {
+    /// Parse the wait directive, it returns `None` if the duration is invalid.
+    pub fn parse_wait(wait_ast: Pairs<'_, Rule>) -> Option<Self> {
+        return wait_ast
+            .into_iter()
+            .filter( |pair| pair.as_rule() == Rule::WAIT )
+            .flat_map( |pair| pair.into_inner() )
+            .find( |inner_pair| inner_pair.as_rule() == Rule::WAIT_DURATION )
+            .and_then( |inner_pair| utils::parse_duration(inner_pair.as_str()) )
+            .map( Directive::Wait );
+    }
+
+    /// Return the directive text.
+    pub fn text(&self) -> String {
+        return match self {
+            Directive::Wait(duration) => format!("wait: {}s", duration.as_secs()),
+            Directive::Pin => "pin:".to_string(),
+            Directive::Silent => "silent:".to_string()
+        };
+    }
+}
@@ -0,0 +1,143 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use std::path::PathBuf;
+use pest::iterators::Pairs;
+
+use super::Rule;
+use crate::limits;
+use crate::markup::ParseMode;
+
+/// The photo file extensions, the other files are sent as documents.
+const PHOTO_EXTENSIONS: [&str; 4] = ["jpg", "jpeg", "png", "webp"];
+
+/// Return the lowercase file extension.
+fn extension(file: &std::path::Path) -> String {
+    return file
+        .extension()
+        .map( |extension| extension.to_string_lossy().to_lowercase() )
+        .unwrap_or_default();
+}
+
+/// The media type.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum MediaKind {
+    /// The photo (`img:`), it is sent by `sendPhoto`.
+    Photo,
+    /// The document (`doc:`), it is sent by `sendDocument`.
+    Document
+}
+
+impl MediaKind {
+    /// Return the media type of the file by its extension.
+    pub fn from_file(file: &std::path::Path) -> Self {
+        if PHOTO_EXTENSIONS.contains(&extension(file).as_str()) {
+            return MediaKind::Photo;
+        }
+        return MediaKind::Document;
+    }
+
+    /// Return the Telegram method that sends the media.
+    pub fn method(&self) -> &str {
+        return match self {
+            MediaKind::Photo => "sendPhoto",
+            MediaKind::Document => "sendDocument"
+        };
+    }
+
+    /// Return the Telegram field of the uploaded file, it is also the media type in the albums.
+    pub fn field(&self) -> &str {
+        return match self {
+            MediaKind::Photo => "photo",
+            MediaKind::Document => "document"
+        };
+    }
+
+    /// Return the max size of the uploaded file.
+    pub fn max_size(&self) -> u64 {
+        return match self {
+            MediaKind::Photo => limits::PHOTO_SIZE,
+            MediaKind::Document => limits::DOCUMENT_SIZE
+        };
+    }
+}
+
+/// The media section, it is a local file that will be uploaded.
+pub struct Media {
+    pub kind: MediaKind,
+    /// The file path as it is written in the .mcq file.
+    pub path: String,
+    /// The file path, it is resolved relative to the .mcq file.
+    pub file: PathBuf,
+    /// The media caption.
+    pub caption: Option<String>,
+    /// The caption parse mode, it is the messages parse mode.
+    pub parse_mode: ParseMode
+}
+
+impl Media {
+    pub fn new() -> Self {
+        return Self {
+            kind: MediaKind::Photo,
+            path: String::new(),
+            file: PathBuf::new(),
+            caption: None,
+            parse_mode: ParseMode::Markdown
+        };
+    }
+
+    /// Parse the media line, the path is resolved relative to the `dir` of the .mcq file.
+    pub fn parse(&mut self, media_ast: Pairs<'_, Rule>, dir: &std::path::Path) {
+        media_ast
+            .flatten()
+            .for_each( |pair| match pair.as_rule() {
+                Rule::DOCUMENT_START => self.kind = MediaKind::Document,
+                Rule::MEDIA_PATH => {
+                    self.path = pair.as_str().trim().to_string();
+                    self.file = dir.join(&self.path);
+                },
+                Rule::MEDIA_CAPTION => self.caption = Some(pair.as_str().trim().to_string()),
+                _ => {}
+            });
+    }
+
+    /// Return the file size, it is `None` if the file is NOT found.
+    pub fn size(&self) -> Option<u64> {
+        return std::fs::metadata(&self.file)
+            .ok()
+            .filter( |metadata| metadata.is_file() )
+            .map( |metadata| metadata.len() );
+    }
+
+    /// Return the file size and the modified time (unix time), they are added to the 
+    /// section hash, so the file that is replaced under the same path is sent again.
+    pub fn stamp(&self) -> String {
+        return std::fs::metadata(&self.file)
+            .map( |metadata| {
+                let modified = metadata
+                    .modified()
+                    .ok()
+                    .and_then( |time| time.duration_since(std::time::UNIX_EPOCH).ok() )
+                    .map( |duration| duration.as_secs() )
+                    .unwrap_or(0);
+                format!("{} {}", metadata.len(), modified)
+            })
+            .unwrap_or_default();
+    }
+
+    /// Check if the photo is a JPG, PNG or WEBP file, the documents can be any file.
+    pub fn is_type_valid(&self) -> bool {
+        if self.kind == MediaKind::Document {
+            return true;
+        }
+        return PHOTO_EXTENSIONS.contains(&extension(&self.file).as_str());
+    }
+
+    /// Check the caption length.
+    pub fn is_caption_valid(&self) -> bool {
+        if let Some(caption) = &self.caption {
+            return limits::CAPTION.is_valid(limits::len(caption, self.parse_mode));
+        }
+        return true;
+    }
+}
@@ -1,22 +1,152 @@
 // This file is part of mcqp project, licensed under the GPL v3.
 // See the LICENSE file for full license details.
 
+use pest::iterators::Pairs;
 use telemark::parser::mdv1;
 use telemark::parser::enums::MarkdownErrorType;
-use telemark::parser::types::MarkdownError;
+
+use super::Rule;
+use crate::markup::{self, MarkupError, ParseMode};
+use crate::limits;
+
+/// The link button under the message (`[[text](url)]`).
+pub struct Button {
+    pub text: String,
+    pub url: String
+}
+
+impl Button {
+    /// Check if the URL is an absolute `http`, `https` or `tg` URL.
+    pub fn is_url_valid(&self) -> bool {
+        if self.url.chars().any(char::is_whitespace) {
+            return false;
+        }
+        return reqwest::Url::parse(&self.url).is_ok_and( |url| {
+            ["http", "https", "tg"].contains(&url.scheme()) 
+                && url.host_str().is_some_and( |host| !host.is_empty() )
+        });
+    }
+
+    /// Check the button text length.
+    pub fn is_text_valid(&self) -> bool {
+        return limits::BUTTON_TEXT.is_valid(self.text.encode_utf16().count());
+    }
+}
 
 pub struct Message {
     /// The message body
     pub m: String,
+    /// The message parse mode.
+    pub parse_mode: ParseMode,
+    /// The message is written in CommonMark (`m:md(`).
+    pub is_commonmark: bool,
+    /// Disable the link preview (`nopreview`).
+    pub is_nopreview: bool,
+    /// Send the message without notification (`silent`).
+    pub is_silent: bool,
+    /// Protect the message from forwarding and saving (`protect`).
+    pub is_protected: bool,
+    /// Reply to the previously sent section (`reply_to: previous`).
+    pub is_reply: bool,
+    /// The link buttons rows, they are sent with the last part of the message.
+    pub buttons: Vec<Vec<Button>>,
+    /// The message parts if the long message is split.
+    parts: Vec<String>
 }
 impl Message {
     pub fn new() -> Self {
-        return Self { m: String::new() };
+        return Self { 
+            m: String::new(), 
+            parse_mode: ParseMode::Markdown, 
+            is_commonmark: false, 
+            is_nopreview: false,
+            is_silent: false,
+            is_protected: false,
+            is_reply: false,
+            buttons: Vec::new(), 
+            parts: Vec::new() 
+        };
+    }
+
+    /// Parse the message header options (`m:(parse_mode: html, nopreview)`), 
+    /// it returns the parse mode of the block if it is set, or the name 
+    /// of the option that is set twice.
+    pub fn parse_options(&mut self, header_ast: Pairs<'_, Rule>) -> Result<Option<ParseMode>, String> {
+        let mut parse_mode = None;
+        let mut names: Vec<String> = Vec::new();
+        let mut duplicate = None;
+        header_ast
+            .flatten()
+            .for_each( |pair| match pair.as_rule() {
+                Rule::MESSAGE_OPTION => {
+                    let name = pair.as_str().split(':').next().unwrap_or_default().trim().to_lowercase();
+                    if names.contains(&name) {
+                        duplicate.get_or_insert(name);
+                    } else {
+                        names.push(name);
+                    }
+                },
+                Rule::MESSAGE_COMMONMARK_START => self.is_commonmark = true,
+                Rule::PARSE_MODE_VALUE => parse_mode = ParseMode::from_name(pair.as_str()),
+                Rule::MESSAGE_NOPREVIEW => self.is_nopreview = true,
+                Rule::MESSAGE_SILENT => self.is_silent = true,
+                Rule::MESSAGE_PROTECT => self.is_protected = true,
+                Rule::MESSAGE_REPLY_TO => self.is_reply = true,
+                _ => {}
+            });
+        if let Some(name) = duplicate {
+            return Err(name);
+        }
+        return Ok(parse_mode);
     }
 
-    /// The markdown parser.
+    /// Return the names of the set flags and the reply option.
+    pub fn flags(&self) -> Vec<&str> {
+        return [
+            (self.is_nopreview, "nopreview"), 
+            (self.is_silent, "silent"), 
+            (self.is_protected, "protect"), 
+            (self.is_reply, "reply_to: previous")
+        ]
+            .into_iter()
+            .filter( |(is_set, _)| *is_set )
+            .map( |(_, name)| name )
+            .collect();
+    }
+
+    /// Parse the buttons line (`[[Slides](https://...)] [[Form](https://...)]`) as a new row.
+    pub fn parse_buttons(&mut self, row_ast: Pairs<'_, Rule>) {
+        let mut row = Vec::new();
+        row_ast
+            .flatten()
+            .for_each( |pair| match pair.as_rule() {
+                Rule::BUTTON_TEXT => row.push(Button { text: pair.as_str().trim().to_string(), url: String::new() }),
+                Rule::BUTTON_URL => if let Some(button) = row.last_mut() {
+                    button.url = pair.as_str().trim().to_string();
+                },
+                _ => {}
+            });
+        self.buttons.push(row);
+    }
+
+    /// Return the number of the buttons in all rows.
+    pub fn buttons_len(&self) -> usize {
+        return self.buttons.iter().map( |row| row.len() ).sum();
+    }
+
+    /// Convert the CommonMark message to MarkdownV2.
+    pub fn parse_commonmark(&mut self, msg: String) {
+        self.parse_mode = ParseMode::MarkdownV2;
+        self.m = markup::fix(&markup::commonmark::to_markdown_v2(msg.trim()), self.parse_mode);
+    }
+
+    /// The markup parser, it fixes the markup errors.
     pub fn parse(&mut self, msg: String) {
         self.m = msg.clone().trim().to_string();
+        if self.parse_mode != ParseMode::Markdown {
+            self.m = markup::fix(&self.m, self.parse_mode);
+            return;
+        }
         if let Err(err) = mdv1::parser(&msg) {
             match err.err() {
                 MarkdownErrorType::BacktickOpen  => self.m.push('`'),
@@ -30,14 +160,38 @@ impl Message {
     }
 
     /// Parse the message and throw the errors.
-    pub fn parse_with_result(&mut self, msg: String) -> Result<(), MarkdownError> {
-        mdv1::parser(&msg)?; // Throw the errors to the up level.
+    pub fn parse_with_result(&mut self, msg: String) -> Result<(), MarkupError> {
+        markup::parse(&msg, self.parse_mode)?; // Throw the errors to the up level.
         self.m = msg.clone().trim().to_string();
         return Ok(());
     }
 
+    /// Return the body length as Telegram measures it.
+    pub fn len(&self) -> usize {
+        return limits::len(&self.m, self.parse_mode);
+    }
+
     /// Check the body length.
     pub fn is_valid(&self) -> bool {
-        return self.m.len() > 0;
+        return limits::MESSAGE.is_valid(self.len());
+    }
+
+    /// Split the long message into parts, it returns `false` if the message can NOT be split.
+    pub fn split(&mut self) -> bool {
+        return match limits::split(&self.m, self.parse_mode, &limits::MESSAGE) {
+            Some(parts) => {
+                self.parts = parts;
+                true
+            },
+            None => false
+        };
     }
-}
\ No newline at end of file
+
+    /// Return the message parts to send, the message is one part if it is NOT split.
+    pub fn parts(&self) -> Vec<String> {
+        if self.parts.is_empty() {
+            return vec![self.m.clone()];
+        }
+        return self.parts.clone();
+    }
+}
@@ -0,0 +1,82 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use pest::iterators::Pairs;
+use super::Rule;
+
+/// The section metadata, it is written as comments before the section:
+/// ```
+/// // @id: algebra-01
+/// // @tags: algebra, hard
+/// // @difficulty: hard
+/// q: 2x = 4, x = ?
+///     1
+///     2 *
+/// ```
+#[derive(Clone)]
+pub struct Meta {
+    /// The section stable id.
+    pub id: Option<String>,
+    /// The section tags.
+    pub tags: Vec<String>,
+    /// The section difficulty.
+    pub difficulty: Option<String>
+}
+
+impl Meta {
+    pub fn new() -> Self {
+        return Self {
+            id: None,
+            tags: Vec::new(),
+            difficulty: None
+        };
+    }
+
+    /// Check if there is no metadata.
+    pub fn is_empty(&self) -> bool {
+        return self.id.is_none() && self.tags.is_empty() && self.difficulty.is_none();
+    }
+
+    /// Check if the section has the tag, the tags are not case sensitive.
+    pub fn has_tag(&self, tag: &str) -> bool {
+        return self.tags
+            .iter()
+            .any( |t| t.to_lowercase() == tag.to_lowercase() );
+    }
+
+    /// Parse the metadata line. The `@tags` will be added to the 
+    /// previous tags, and `@id` and `@difficulty` will override 
+    /// the previous values.
+    pub fn parse(&mut self, meta_ast: Pairs<'_, Rule>) {
+        meta_ast
+            .into_iter()
+            .filter( |pair| pair.as_rule() == Rule::META )
+            .for_each( |pair| {
+                let mut name = Rule::META;
+                let mut value = String::new();
+                pair
+                    .into_inner()
+                    .for_each( |inner_pair| {
+                        match inner_pair.as_rule() {
+                            Rule::META_VALUE => value = inner_pair.as_str().trim().to_string(),
+                            Rule::META_START => {},
+                            rule => name = rule
+                        }
+                    });
+                match name {
+                    Rule::META_TAGS_NAME => {
+                        value
+                            .split(',')
+                            .map( |tag| tag.trim() )
+                            .filter( |tag| !tag.is_empty() && !self.has_tag(tag) )
+                            .collect::<Vec<&str>>()
+                            .into_iter()
+                            .for_each( |tag| self.tags.push(tag.to_string()) );
+                    },
+                    Rule::META_DIFFICULTY_NAME => self.difficulty = Some(value),
+                    Rule::META_ID_NAME => self.id = Some(value),
+                    _ => {}
+                }
+            });
+    }
+}
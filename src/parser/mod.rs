@@ -6,6 +6,16 @@ mod poll_parser;
 mod question_parser;
 mod config_parser;
 mod message_parser;
+mod meta_parser;
+mod directive_parser;
+mod media_parser;
+mod album_parser;
+
+pub use directive_parser::Directive;
+pub use question_parser::Question;
+pub use message_parser::Button;
+pub use media_parser::Media;
+pub use album_parser::Album;
 
 // 3-party packages
 use pest::Parser;
@@ -16,9 +26,65 @@ use pest::error::{
 };
 
 // The MCQP modules
-use crate::file::FileReader;
+use crate::file::{self, FileReader};
 use crate::display::DisplaySyntaxError;
+use crate::markup::{self, EntityKind, MarkupError, MarkupFix, ParseMode};
 use crate::log::Log;
+use crate::limits;
+use crate::utils;
+
+/// Check the file state and then parse it, if the file is not found, 
+/// not .mcq file or has syntax errors, this will print an error 
+/// and exit the program.
+/// 
+/// ### Example:
+/// ```
+/// let logger = Log::new("checker");
+/// let abstraction_tree = parser::parse_file("./dir/to/test.mcq", &logger);
+/// ```
+pub fn parse_file(file: &str, logger: &Log) -> McqpAST {
+    let file_state = file::state(file.to_string());
+    if file_state == file::FileState::NotFound {
+        logger.error("File NOT found!");
+    } else if file_state == file::FileState::NotMcqpFile {
+        logger.error("File type is NOT .mcq!");
+    }
+    let mut abstraction_tree = McqpAST::new(
+        std::path::PathBuf::new().join(file)
+    );
+    abstraction_tree.parse();
+    return abstraction_tree;
+}
+
+/// Parse the file without exiting the program, the syntax errors are still 
+/// displayed but the error is returned, so the long running commands (like 
+/// `mcqp schedule run`) can keep running. The media paths are resolved relative 
+/// to the `base_dir`, so a copy of the file can use the media of the original file.
+///
+/// ### Example:
+/// ```
+/// match parser::try_parse_file("./data/schedule/1.mcq", Path::new("./dir/to")) {
+///     Ok(abstraction_tree) => ...,
+///     Err(err) => logger.warn(&err)
+/// }
+/// ```
+pub fn try_parse_file(file: &str, base_dir: &std::path::Path) -> Result<McqpAST, String> {
+    match file::state(file.to_string()) {
+        file::FileState::NotFound => return Err("File NOT found!".to_string()),
+        file::FileState::NotMcqpFile => return Err("File type is NOT .mcq!".to_string()),
+        _ => {}
+    }
+    let mut abstraction_tree = McqpAST::new(
+        std::path::PathBuf::new().join(file)
+    );
+    abstraction_tree.exit_on_error = false;
+    abstraction_tree.base_dir = base_dir.to_path_buf();
+    abstraction_tree.parse();
+    if abstraction_tree.errors.get() > 0 {
+        return Err(format!("Can not parse the file, found {} errors!", abstraction_tree.errors.get()));
+    }
+    return Ok(abstraction_tree);
+}
 
 /// The .mcq sections types.
 #[derive(PartialEq, Debug)]
@@ -30,7 +96,28 @@ pub enum McqpType {
     /// Multiple choice poll section
     MCPoll,
     /// Message section
-    Message
+    Message,
+    /// Photo or document section (`img:` and `doc:`)
+    Media,
+    /// Photos or documents album section (`album:(`)
+    Album,
+    /// Directive between the sections (`wait:`, `pin:` and `silent:`)
+    Directive
+}
+
+impl McqpType {
+    /// Return the section type name.
+    pub fn name(&self) -> &str {
+        return match self {
+            McqpType::Poll => "poll",
+            McqpType::Question => "question",
+            McqpType::MCPoll => "mcpoll",
+            McqpType::Message => "message",
+            McqpType::Media => "media",
+            McqpType::Album => "album",
+            McqpType::Directive => "directive"
+        };
+    }
 }
 
 /// The .mcq section tree.
@@ -42,7 +129,105 @@ pub struct Mcqp {
     /// The question information.
     pub question: Option<question_parser::Question>,
     /// The message information.
-    pub message: Option<message_parser::Message>
+    pub message: Option<message_parser::Message>,
+    /// The directive information.
+    pub directive: Option<Directive>,
+    /// The media information.
+    pub media: Option<media_parser::Media>,
+    /// The album information.
+    pub album: Option<album_parser::Album>,
+    /// The section number in the file, the first section is 1.
+    pub number: usize,
+    /// The section metadata.
+    pub meta: meta_parser::Meta
+}
+
+impl Mcqp {
+    /// Return the section content hash, it changes only when the section content changes.
+    pub fn hash(&self) -> String {
+        let mut content = format!("{}\n", self._type.name());
+        if let Some(poll) = &self.poll {
+            content += &format!("{}\n{}\n", poll.question(), poll.choices().join("\n"));
+            // The plain text is not added, so the old hashes do not change.
+            if poll.parse_mode() != ParseMode::None {
+                content += &format!("{}\n", poll.parse_mode().name());
+            }
+            // The anonymous polls are the default, so the old hashes do not change.
+            if !poll.is_anonymous() {
+                content += "anonymous = false\n";
+            }
+        }
+        if let Some(question) = &self.question {
+            content += &format!(
+                "{}\n{}\n{}\n{}\n", 
+                question.question(), 
+                question.choices().join("\n"), 
+                question.answer(),
+                question.note().unwrap_or_default()
+            );
+            if question.parse_mode() != ParseMode::None {
+                content += &format!("{}\n", question.parse_mode().name());
+            }
+            if !question.is_anonymous() {
+                content += "anonymous = false\n";
+            }
+            // The overflow message has the question number and the lettered options.
+            if question.is_overflow() {
+                content += &format!("{}\n", question.overflow_message());
+            }
+        }
+        if let Some(message) = &self.message {
+            content += &format!("{}\n", message.m);
+            // The default parse mode is not added, so the old hashes do not change.
+            if message.parse_mode != ParseMode::Markdown {
+                content += &format!("{}\n", message.parse_mode.name());
+            }
+            if !message.flags().is_empty() {
+                content += &format!("{}\n", message.flags().join(", "));
+            }
+            for row in &message.buttons {
+                let row = row
+                    .iter()
+                    .map( |button| format!("[{}]({})", button.text, button.url) )
+                    .collect::<Vec<String>>();
+                content += &format!("{}\n", row.join(" "));
+            }
+        }
+        if let Some(directive) = &self.directive {
+            content += &format!("{}\n", directive.text());
+        }
+        if let Some(media) = &self.media {
+            content += &format!(
+                "{}\n{}\n{}\n{}\n{}\n", 
+                media.kind.field(), 
+                media.path, 
+                media.stamp(),
+                media.caption.clone().unwrap_or_default(),
+                media.parse_mode.name()
+            );
+        }
+        if let Some(album) = &self.album {
+            for item in &album.items {
+                content += &format!(
+                    "{}\n{}\n{}\n{}\n{}\n", 
+                    item.kind.field(), 
+                    item.path, 
+                    item.stamp(),
+                    item.caption.clone().unwrap_or_default(),
+                    item.parse_mode.name()
+                );
+            }
+        }
+        return utils::hash(&content);
+    }
+
+    /// Return the section stable key, it is the section id or 
+    /// the section number (`#<number>`) if there is no id.
+    pub fn key(&self) -> String {
+        return self.meta.id
+            .clone()
+            .unwrap_or_else(|| format!("#{}", self.number));
+    }
 }
 
 #[derive(Parser)]
@@ -57,6 +242,7 @@ struct MCQPParser;
 ///     poll_count: 1,
 ///     question_count: 0,
 ///     message_count: 0,
+///     media_count: 0,
 ///     config: Config {
 ///         counter: 20
 ///     },
@@ -73,7 +259,16 @@ struct MCQPParser;
 ///                 is_mcp: false
 ///             }),
 ///             question: None,
-///             message: None
+///             message: None,
+///             directive: None,
+///             media: None,
+///             album: None,
+///             number: 1,
+///             meta: Meta {
+///                 id: Some("poll-01".to_string()),
+///                 tags: vec!["numbers".to_string()],
+///                 difficulty: None
+///             }
 ///         }
 ///     ]
 /// }
@@ -85,14 +280,26 @@ pub struct McqpAST {
     pub question_count: u16,
     /// The number of messages in the list.
     pub message_count: u16,
+    /// The number of photos, documents and albums in the list.
+    pub media_count: u16,
     /// The list of polls, questions and messages.
     pub mcqps: Vec<Mcqp>,
     /// The MCQP features.
     pub config: config_parser::Config,
     /// The file path.
     file_path: std::path::PathBuf,
+    /// The dir that the media paths are relative to, it is the file dir by default.
+    base_dir: std::path::PathBuf,
     /// The file reader.
-    file_reader: FileReader
+    file_reader: FileReader,
+    /// The metadata of the next section.
+    next_meta: meta_parser::Meta,
+    /// The last metadata line `(line, line_number)`.
+    next_meta_line: (String, usize),
+    /// Exit the program on the first error, otherwise the errors are counted.
+    exit_on_error: bool,
+    /// The number of the found errors.
+    errors: std::cell::Cell<usize>
 }
 
 impl McqpAST {
@@ -101,10 +308,16 @@ impl McqpAST {
             poll_count: 0, 
             question_count: 0, 
             message_count: 0, 
+            media_count: 0,
             mcqps: Vec::new(),
             config: config_parser::Config::new(),
             file_path: file_path.clone(),
-            file_reader: FileReader::new(file_path)
+            base_dir: file_path.parent().unwrap_or(std::path::Path::new("")).to_path_buf(),
+            file_reader: FileReader::new(file_path),
+            next_meta: meta_parser::Meta::new(),
+            next_meta_line: (String::new(), 0),
+            exit_on_error: true,
+            errors: std::cell::Cell::new(0)
         };
     }
 
@@ -113,11 +326,14 @@ impl McqpAST {
         let logger = Log::new("parser");
         while let Some(line) = &self.file_reader.next_line() {
 
-            // Parse the comment.
-            if MCQPParser::parse(Rule::COMMENT, line).is_ok() { continue; }
+            // Parse the section metadata, it must be before the comment.
+            if let Ok(meta_ast) = MCQPParser::parse(Rule::META, line) {
+                self.parse_meta(meta_ast, line, self.file_reader.get_line_number());
+            }
 
-            // Parse the empty line.
-            else if MCQPParser::parse(Rule::EMPTY_LINE, line).is_ok() { continue; }
+            // Skip the comment and the empty line.
+            else if MCQPParser::parse(Rule::COMMENT, line).is_ok()
+                || MCQPParser::parse(Rule::EMPTY_LINE, line).is_ok() { continue; }
 
             // Parse the Poll section.
             else if MCQPParser::parse(Rule::POLL_START, line).is_ok() { 
@@ -151,8 +367,31 @@ impl McqpAST {
             }
 
             // Parse the Message block.
-            else if MCQPParser::parse(Rule::MESSAGE_SATRT, line).is_ok() { 
-                self.parse_message(&line, self.file_reader.get_line_number());
+            else if MCQPParser::parse(Rule::MESSAGE_SATRT, line).is_ok() 
+                || MCQPParser::parse(Rule::MESSAGE_COMMONMARK_START, line).is_ok() { 
+                self.parse_message(line, self.file_reader.get_line_number());
+            }
+
+            // Parse the Media section.
+            else if MCQPParser::parse(Rule::IMAGE_START, line).is_ok() 
+                || MCQPParser::parse(Rule::DOCUMENT_START, line).is_ok() { 
+                self.parse_media(line, self.file_reader.get_line_number());
+            }
+
+            // Parse the Album block.
+            else if MCQPParser::parse(Rule::ALBUM_START, line).is_ok() { 
+                self.parse_album(line, self.file_reader.get_line_number());
+            }
+
+            // Parse the directives.
+            else if MCQPParser::parse(Rule::WAIT_START, line).is_ok() { 
+                self.parse_directive(line, Rule::WAIT, self.file_reader.get_line_number());
+            }
+            else if MCQPParser::parse(Rule::PIN_START, line).is_ok() { 
+                self.parse_directive(line, Rule::PIN, self.file_reader.get_line_number());
+            }
+            else if MCQPParser::parse(Rule::SILENT_START, line).is_ok() { 
+                self.parse_directive(line, Rule::SILENT, self.file_reader.get_line_number());
             }
 
             // Parse any unknown keyword.
@@ -175,12 +414,32 @@ impl McqpAST {
                 self.exit();
             }
         }
+        if let Some(directive) = self.mcqps.last().and_then( |mcqp| mcqp.directive.as_ref() ) {
+            if !matches!(directive, Directive::Wait(_)) {
+                logger.warn(
+                    &format!("Found `{}` in the end of the file, but there is no section after it!", directive.text())
+                );
+                self.exit();
+            }
+        }
+        if !self.next_meta.is_empty() {
+            DisplaySyntaxError::error(
+                "Found metadata but there is no section after it.", 
+                "Expected a section after the metadata, found end of file.", 
+                &self.file_path, 
+                &self.next_meta_line.0, 
+                self.next_meta_line.1, 
+                0
+            );
+            self.exit();
+        }
         logger.info(
             &format!(
-                "found {}/poll and {}/question and {}/message", 
+                "found {}/poll and {}/question and {}/message and {}/media", 
                 self.poll_count,
                 self.question_count,
-                self.message_count
+                self.message_count,
+                self.media_count
             )
         );
     }
@@ -193,13 +452,17 @@ impl McqpAST {
         );
         if let Ok(poll_header_ast) = poll_header_result {
             let mut poll = poll_parser::Poll::new();
+            poll.set_parse_mode(self.config.poll_parse_mode);
             poll.parse_header(poll_header_ast);
+            let question_start = header_line.find(':').map_or(0, |index| index + 1);
+            self.check_markup(&poll.question(), poll.parse_mode(), header_line, header_line_number, question_start);
+            self.check_poll_entities(&poll.question(), poll.parse_mode(), header_line_number);
             if !poll.is_question_valid() {
                 DisplaySyntaxError::error(
                     "The poll question length is not between 1 to 255 characher.", 
                     &format!(
                         "Expected poll question to be between 1 to 255 characher, found {} characher.", 
-                        poll.question().chars().count()
+                        poll.question_len()
                     ), 
                     &self.file_path, 
                     header_line, 
@@ -212,12 +475,15 @@ impl McqpAST {
             while let Some(line) = &self.file_reader.next_line() {
                 if let Ok(option_ast) = MCQPParser::parse(Rule::OPTION, line) {
                     poll.parse_option(option_ast);
+                    let option = poll.choices().pop().unwrap_or_default();
+                    self.check_markup(&option, poll.parse_mode(), line, self.file_reader.get_line_number(), 0);
+                    self.check_poll_entities(&option, poll.parse_mode(), self.file_reader.get_line_number());
                     if !poll.is_last_option_valid() {
                         DisplaySyntaxError::error(
                             "The option length is not between 1 to 100 characher.", 
                             &format!(
                                 "Expected the option length between 1 to 100 characher, found {} characher.",
-                                line.trim().chars().count()
+                                limits::len(&option, self.config.poll_parse_mode)
                             ), 
                             &self.file_path,
                             line, 
@@ -231,6 +497,8 @@ impl McqpAST {
                 break;
             }
             self.file_reader.back_to_previous();
+            // The markup errors are escaped if `md_err` is NOT set.
+            poll.fix_markup();
             if !poll.is_options_valid() {
                 DisplaySyntaxError::error(
                     "The number of the poll options is not between 2 to 10 option.", 
@@ -256,12 +524,19 @@ impl McqpAST {
                 }
                 self.config.counter.1 += 1;
             }
+            poll.set_anonymous(self.config.anonymous);
             self.poll_count += 1;
+            let meta = self.take_meta();
             self.mcqps.push(Mcqp {
                 _type: if is_mcpoll { McqpType::MCPoll } else { McqpType::Poll },
                 poll: Some(poll),
                 question: None,
-                message: None
+                message: None,
+                directive: None,
+                media: None,
+                album: None,
+                number: self.sections_len() + 1,
+                meta
             });
         } 
         else if let Err(error) = poll_header_result {
@@ -296,8 +571,18 @@ impl McqpAST {
         );
         if let Ok(question_header_ast) = question_header_result {
             let mut question = question_parser::Question::new();
+            question.set_parse_mode(self.config.poll_parse_mode);
             question.parse_header(question_header_ast);
-            if !question.is_question_valid() {
+            let question_start = header_line.find(':').map_or(0, |index| index + 1);
+            self.check_markup(&question.question(), question.parse_mode(), header_line, header_line_number, question_start);
+            self.check_poll_entities(&question.question(), question.parse_mode(), header_line_number);
+            if let Some(note) = question.note() {
+                let note_start = header_line.find("<NOTE:").map_or(0, |index| index + 6);
+                self.check_markup(&note, question.parse_mode(), header_line, header_line_number, note_start);
+            }
+            // The long question is sent in the overflow message if the `overflow` config is set.
+            let is_overflow = self.config.overflow && question.is_question_overflow();
+            if !question.is_question_valid() && !is_overflow {
                 DisplaySyntaxError::error(
                     "The question length is not between 1 to 255 characher.", 
                     &format!(
@@ -315,7 +600,10 @@ impl McqpAST {
                 if !question.is_note_valid() {
                     DisplaySyntaxError::error(
                         "The length of the note is not between 1 to 200 characher", 
-                        "Expected the note length between 1 to 200 characher.",
+                        &format!(
+                            "Expected the note length between 1 to 200 characher, found {} characher.",
+                            limits::len(&question.note().unwrap_or_default(), question.parse_mode())
+                        ),
                         &self.file_path, 
                         header_line, 
                         header_line_number, 
@@ -328,12 +616,16 @@ impl McqpAST {
             while let Some(line) = &self.file_reader.next_line() {
                 if let Ok(option_ast) = MCQPParser::parse(Rule::OPTION, line) {
                     question.parse_option(option_ast);
-                    if !question.is_last_option_valid() {
+                    let option = question.choices().pop().unwrap_or_default();
+                    self.check_markup(&option, question.parse_mode(), line, self.file_reader.get_line_number(), 0);
+                    self.check_poll_entities(&option, question.parse_mode(), self.file_reader.get_line_number());
+                    let is_overflow = self.config.overflow && question.is_last_option_overflow();
+                    if !question.is_last_option_valid() && !is_overflow {
                         DisplaySyntaxError::error(
                             "The option length is not between 1 to 100 characher.", 
                             &format!(
                                 "Expected the option length between 1 to 100 characher, found {} characher.",
-                                line.trim().chars().count()
+                                limits::len(&option, self.config.poll_parse_mode)
                             ), 
                             &self.file_path,
                             line, 
@@ -347,6 +639,8 @@ impl McqpAST {
                 break;
             }
             self.file_reader.back_to_previous();
+            // The markup errors are escaped if `md_err` is NOT set.
+            question.fix_markup();
             if !question.is_options_valid() {
                 DisplaySyntaxError::error(
                     "The number of the question options is not between 2 to 10 option or there is no correct answer.", 
@@ -358,8 +652,11 @@ impl McqpAST {
                 );
                 self.exit();
             }
+            question.set_number((self.poll_count + self.question_count) as usize + 1);
             if self.config.counter.0 {
-                if !question.add_count(self.config.counter.1) {
+                question.set_number(self.config.counter.1);
+                // The counter of the long question is added to the overflow message.
+                if !question.is_question_overflow() && !question.add_count(self.config.counter.1) {
                     DisplaySyntaxError::error(
                         "You can not add counter.", 
                         "The length of the question + counter exit 255.", 
@@ -372,12 +669,30 @@ impl McqpAST {
                 }
                 self.config.counter.1 += 1;
             }
+            if question.is_overflow() && limits::len(&question.overflow_message(), question.parse_mode()) > limits::MESSAGE.max {
+                DisplaySyntaxError::error(
+                    &format!("The overflow message length is more than {} characher.", limits::MESSAGE.max), 
+                    "The question and the options are sent in the overflow message, it must be in the message limit.", 
+                    &self.file_path, 
+                    header_line, 
+                    header_line_number, 
+                    0
+                );
+                self.exit();
+            }
+            question.set_anonymous(self.config.anonymous);
             self.question_count += 1;
+            let meta = self.take_meta();
             self.mcqps.push(Mcqp { 
                 _type: McqpType::Question, 
                 poll: None, 
                 question: Some(question), 
-                message: None 
+                message: None,
+                directive: None,
+                media: None,
+                album: None,
+                number: self.sections_len() + 1,
+                meta
             });
         } 
         else if let Err(error) = question_header_result {
@@ -386,7 +701,7 @@ impl McqpAST {
                 _ => 0
             };
             if let ParsingError { negatives, .. } = error.variant {
-                if negatives.len() > 0 {
+                if !negatives.is_empty() {
                     DisplaySyntaxError::error(
                         "Unexpected start of note.", 
                         "The question must be in the first not the note block.", 
@@ -495,22 +810,87 @@ impl McqpAST {
 
     /// The Message parser.
     fn parse_message(&mut self, message_line: &str, message_line_number: usize) {
-        let mut msg = String::new();
+        let mut lines = Vec::new();
         while let Some(line) = &self.file_reader.next_line() {
             if MCQPParser::parse(Rule::MESSAGE_END, line).is_ok() {
                 break;
             }
-            msg += &format!("{}\n", line);
+            lines.push(line.clone());
         }
         let mut message = message_parser::Message::new();
-        if self.config.md_err {
+        message.parse_mode = self.config.parse_mode;
+        match MCQPParser::parse(Rule::MESSAGE_HEADER, message_line) {
+            Ok(header_ast) => {
+                match message.parse_options(header_ast) {
+                    Err(name) => {
+                        DisplaySyntaxError::error(
+                            "Duplicate message option.", 
+                            &format!("Expected the `{}` option once.", name), 
+                            &self.file_path, 
+                            message_line, 
+                            message_line_number, 
+                            message_line.to_lowercase().rfind(&name).unwrap_or(0)
+                        );
+                        self.exit();
+                    },
+                    Ok(Some(_)) if message.is_commonmark => {
+                        DisplaySyntaxError::error(
+                            "The CommonMark message can NOT have a parse mode.", 
+                            "The `m:md(` message is always sent as MarkdownV2.", 
+                            &self.file_path, 
+                            message_line, 
+                            message_line_number, 
+                            0
+                        );
+                        self.exit();
+                    },
+                    Ok(Some(parse_mode)) => message.parse_mode = parse_mode,
+                    Ok(None) => message.is_commonmark |= self.config.commonmark
+                }
+            },
+            Err(error) => {
+                let error_position = match error.location {
+                    Pos(postion) => postion,
+                    _ => 0
+                };
+                DisplaySyntaxError::error(
+                    "Invalid message options.", 
+                    "Expected an option like `parse_mode: html`, `nopreview`, `silent`, `protect` or `reply_to: previous` followed by `)`.", 
+                    &self.file_path, 
+                    message_line, 
+                    message_line_number, 
+                    error_position
+                );
+                self.exit();
+            }
+        }
+        if message.is_reply && self.sections_len() == 0 {
+            DisplaySyntaxError::error(
+                "There is no section before the message to reply to.", 
+                "Expected a section before the message with `reply_to: previous`.", 
+                &self.file_path, 
+                message_line, 
+                message_line_number, 
+                message_line.to_lowercase().find("reply_to").unwrap_or(0)
+            );
+            self.exit();
+        }
+        let body_len = self.parse_message_buttons(&mut message, &lines, message_line, message_line_number);
+        let msg = lines[..body_len]
+            .iter()
+            .map( |line| format!("{}\n", line) )
+            .collect::<String>();
+        if message.is_commonmark {
+            // The converted message is always valid MarkdownV2.
+            message.parse_commonmark(msg);
+        } else if self.config.md_err {
             // To Display the Markdown error.
             if let Err(err) = message.parse_with_result(msg.clone()) {
                 let mut li: usize = 0;
                 let mut position: usize = 0;
                 for (ci, c) in msg.chars().enumerate() {
                     position += 1;
-                    if ci == err.offset() { break; }
+                    if ci == err.offset { break; }
                     if c == '\n' {
                         position = 0;
                         li += 1;
@@ -519,27 +899,45 @@ impl McqpAST {
                 // Error line number.
                 let line_number = message_line_number + li + 1;
                 let err_line = msg.split('\n').collect::<Vec<&str>>()[li];
+                self.markup_error(&err, err_line, line_number, position - 1);
+            }
+        } else {
+            message.parse(msg);
+        }
+        let is_long = message.len() > limits::MESSAGE.max;
+        if is_long && self.config.split_long_messages {
+            if !message.split() {
                 DisplaySyntaxError::error(
-                    "Markdown unclosed.", 
-                    "This must be escaped or closed.", 
+                    "The long message can NOT be split.", 
+                    "Expected a paragraph or a line boundary outside the entities and the code blocks.", 
                     &self.file_path, 
-                    err_line,
-                    line_number, 
-                    position-1
-                );
-                DisplaySyntaxError::fix_add(
-                    "Escape the char to use it.", 
-                    err_line, 
-                    "\\", 
-                    line_number, 
-                    position-1
+                    message_line, 
+                    message_line_number, 
+                    0
                 );
                 self.exit();
             }
-        } else {
-            message.parse(msg);
-        }
-        if !message.is_valid() {
+            Log::new("parser").warn(&format!(
+                "The message in the line {} is more than {} characher, it will be sent as {} messages.",
+                message_line_number,
+                limits::MESSAGE.max,
+                message.parts().len()
+            ));
+        } else if is_long {
+            DisplaySyntaxError::error(
+                &format!("The message length is more than {} characher.", limits::MESSAGE.max), 
+                &format!(
+                    "Expected the message length to be at most {} characher, found {} characher. Use `split_long_messages = true` to split it.", 
+                    limits::MESSAGE.max,
+                    message.len()
+                ), 
+                &self.file_path, 
+                message_line, 
+                message_line_number, 
+                0
+            );
+            self.exit();
+        } else if !message.is_valid() {
             DisplaySyntaxError::error(
                 "Found message block but there is no message!", 
                 "There is no message", 
@@ -550,18 +948,562 @@ impl McqpAST {
             );
             self.exit();
         }
+        let meta = self.take_meta();
         self.mcqps.push(Mcqp { 
             _type: McqpType::Message, 
             poll: None, 
             question: None, 
-            message: Some(message)
+            message: Some(message),
+            directive: None,
+            media: None,
+            album: None,
+            number: self.sections_len() + 1,
+            meta
         });
         self.message_count += 1;
     }
 
-    /// Exit the program with `can not parse the file` error.
+    /// Parse the link buttons lines in the end of the message block, 
+    /// it returns the number of the message body lines.
+    fn parse_message_buttons(
+        &self, 
+        message: &mut message_parser::Message, 
+        lines: &[String], 
+        message_line: &str, 
+        message_line_number: usize
+    ) -> usize {
+        let mut body_len = lines.len();
+        for (index, line) in lines.iter().enumerate().rev() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let Ok(row_ast) = MCQPParser::parse(Rule::BUTTONS_ROW, line) else {
+                break;
+            };
+            message.parse_buttons(row_ast);
+            body_len = index;
+            let line_number = message_line_number + index + 1;
+            let row = message.buttons.last().unwrap();
+            for button in row {
+                let position = |text: &str| line
+                    .find(&format!("[{}", text))
+                    .map_or(0, |index| line[..index].chars().count() + 1);
+                if !button.is_url_valid() {
+                    DisplaySyntaxError::error(
+                        "Invalid button URL.", 
+                        "Expected an absolute `http`, `https` or `tg` URL like `https://example.com`.", 
+                        &self.file_path, 
+                        line, 
+                        line_number, 
+                        line.find(&format!("]({}", button.url)).map_or(0, |index| line[..index].chars().count() + 2)
+                    );
+                    self.exit();
+                }
+                if !button.is_text_valid() {
+                    DisplaySyntaxError::error(
+                        &format!("The button text length is not between 1 to {} characher.", limits::BUTTON_TEXT.max), 
+                        &format!(
+                            "Expected the button text length between 1 to {} characher, found {} characher.", 
+                            limits::BUTTON_TEXT.max,
+                            button.text.encode_utf16().count()
+                        ), 
+                        &self.file_path, 
+                        line, 
+                        line_number, 
+                        position(&button.text)
+                    );
+                    self.exit();
+                }
+            }
+            if row.len() > limits::ROW_BUTTONS {
+                DisplaySyntaxError::error(
+                    &format!("The buttons row has more than {} button.", limits::ROW_BUTTONS), 
+                    &format!("Expected at most {} button in the line, found {} button.", limits::ROW_BUTTONS, row.len()), 
+                    &self.file_path, 
+                    line, 
+                    line_number, 
+                    0
+                );
+                self.exit();
+            }
+        }
+        // The rows are parsed from the last line.
+        message.buttons.reverse();
+        if message.buttons_len() > limits::BUTTONS {
+            DisplaySyntaxError::error(
+                &format!("The message has more than {} button.", limits::BUTTONS), 
+                &format!("Expected at most {} button in the message, found {} button.", limits::BUTTONS, message.buttons_len()), 
+                &self.file_path, 
+                message_line, 
+                message_line_number, 
+                0
+            );
+            self.exit();
+        }
+        return body_len;
+    }
+
+    /// The Media parser (`img:` and `doc:`), the file must exist when the file is parsed.
+    fn parse_media(&mut self, media_line: &str, media_line_number: usize) {
+        let media_result = MCQPParser::parse(Rule::MEDIA, media_line);
+        if let Ok(media_ast) = media_result {
+            let mut media = media_parser::Media::new();
+            media.parse(media_ast, &self.base_dir);
+            self.check_media(&mut media, media_line, media_line_number, "<CAPTION:");
+            let meta = self.take_meta();
+            self.mcqps.push(Mcqp { 
+                _type: McqpType::Media, 
+                poll: None, 
+                question: None, 
+                message: None,
+                directive: None,
+                media: Some(media),
+                album: None,
+                number: self.sections_len() + 1,
+                meta
+            });
+            self.media_count += 1;
+        }
+        else if let Err(error) = media_result {
+            let error_position = match error.location {
+                Pos(postion) => postion,
+                _ => 0
+            };
+            DisplaySyntaxError::error(
+                "Invalid media section.", 
+                "Expected a file path like `img: ./diagram.png <CAPTION: The caption>`.", 
+                &self.file_path, 
+                media_line, 
+                media_line_number, 
+                error_position
+            );
+            self.exit();
+        }
+    }
+
+    /// The Album parser, every line in the block is an item (`./a.png | The caption`).
+    fn parse_album(&mut self, album_line: &str, album_line_number: usize) {
+        let dir = self.base_dir.clone();
+        let mut album = album_parser::Album::new();
+        let mut is_closed = false;
+        while let Some(line) = &self.file_reader.next_line() {
+            if MCQPParser::parse(Rule::ALBUM_END, line).is_ok() {
+                is_closed = true;
+                break;
+            }
+            // The empty lines and the comments.
+            if MCQPParser::parse(Rule::EMPTY_LINE, line).is_ok() || MCQPParser::parse(Rule::COMMENT, line).is_ok() { 
+                continue; 
+            }
+            let line_number = self.file_reader.get_line_number();
+            match MCQPParser::parse(Rule::ALBUM_ITEM, line) {
+                Ok(item_ast) => {
+                    let mut item = album_parser::Album::parse_item(item_ast, &dir);
+                    self.check_media(&mut item, line, line_number, "|");
+                    album.items.push(item);
+                },
+                Err(error) => {
+                    let error_position = match error.location {
+                        Pos(postion) => postion,
+                        _ => 0
+                    };
+                    DisplaySyntaxError::error(
+                        "Invalid album item.", 
+                        "Expected a file path like `./diagram.png | The caption`.", 
+                        &self.file_path, 
+                        line, 
+                        line_number, 
+                        error_position
+                    );
+                    self.exit();
+                }
+            }
+        }
+        if !is_closed {
+            DisplaySyntaxError::error(
+                "The album block is NOT closed.", 
+                "Expected `):enda` in the end of the album block, found end of file.", 
+                &self.file_path, 
+                album_line, 
+                album_line_number, 
+                0
+            );
+            self.exit();
+        }
+        if !album.is_valid() {
+            DisplaySyntaxError::error(
+                "The number of the album items is not between 2 to 10 item.", 
+                &format!("Expected 2 to 10 item in the album, found {} item.", album.items.len()), 
+                &self.file_path, 
+                album_line, 
+                album_line_number, 
+                0
+            );
+            self.exit();
+        }
+        if !album.is_compatible() {
+            DisplaySyntaxError::error(
+                "The album can NOT mix the photos and the documents.", 
+                "Expected only photos or only documents in the album.", 
+                &self.file_path, 
+                album_line, 
+                album_line_number, 
+                0
+            );
+            self.exit();
+        }
+        let meta = self.take_meta();
+        self.mcqps.push(Mcqp { 
+            _type: McqpType::Album, 
+            poll: None, 
+            question: None, 
+            message: None,
+            directive: None,
+            media: None,
+            album: Some(album),
+            number: self.sections_len() + 1,
+            meta
+        });
+        self.media_count += 1;
+    }
+
+    /// Check the media file (existence, size and type) and parse its caption, the 
+    /// caption is searched in the line after the `caption_start` text.
+    fn check_media(&self, media: &mut media_parser::Media, line: &str, line_number: usize, caption_start: &str) {
+        media.parse_mode = self.config.parse_mode;
+        let path_position = line
+            .find(&media.path)
+            .map( |index| line[..index].chars().count() )
+            .unwrap_or(0);
+        match media.size() {
+            None => {
+                DisplaySyntaxError::error(
+                    "The media file is NOT found.", 
+                    &format!("Expected a file at `{}`, the path is relative to the .mcq file.", media.file.display()), 
+                    &self.file_path, 
+                    line, 
+                    line_number, 
+                    path_position
+                );
+                self.exit();
+            },
+            Some(size) if size > media.kind.max_size() => {
+                DisplaySyntaxError::error(
+                    "The media file is too big.", 
+                    &format!(
+                        "Expected the {} size to be at most {} MB, found {:.1} MB.", 
+                        media.kind.field(),
+                        media.kind.max_size() / 1024 / 1024,
+                        size as f64 / 1024.0 / 1024.0
+                    ), 
+                    &self.file_path, 
+                    line, 
+                    line_number, 
+                    path_position
+                );
+                self.exit();
+            },
+            Some(_) => {}
+        }
+        if !media.is_type_valid() {
+            DisplaySyntaxError::error(
+                "The photo type is NOT supported.", 
+                "Expected a JPG, PNG or WEBP photo, use `doc:` to send the other files.", 
+                &self.file_path, 
+                line, 
+                line_number, 
+                path_position
+            );
+            self.exit();
+        }
+        if let Some(caption) = media.caption.clone() {
+            // The caption follows the messages config.
+            if self.config.commonmark {
+                media.parse_mode = ParseMode::MarkdownV2;
+                media.caption = Some(markup::fix(&markup::commonmark::to_markdown_v2(&caption), media.parse_mode));
+            } else {
+                let caption_start = line.find(caption_start).map_or(0, |index| index + caption_start.len());
+                self.check_markup(&caption, media.parse_mode, line, line_number, caption_start);
+                media.caption = Some(markup::fix(&caption, media.parse_mode));
+            }
+            if !media.is_caption_valid() {
+                DisplaySyntaxError::error(
+                    "The caption length is not between 1 to 1024 characher.", 
+                    &format!(
+                        "Expected the caption length between 1 to 1024 characher, found {} characher.",
+                        limits::len(&media.caption.clone().unwrap_or_default(), media.parse_mode)
+                    ), 
+                    &self.file_path, 
+                    line, 
+                    line_number, 
+                    0
+                );
+                self.exit();
+            }
+        }
+    }
+
+    /// The directives parser (`wait:`, `pin:` and `silent:`).
+    fn parse_directive(&mut self, line: &str, rule: Rule, line_number: usize) {
+        let directive_result = MCQPParser::parse(rule, line);
+        let directive = match directive_result {
+            Ok(wait_ast) if rule == Rule::WAIT => Directive::parse_wait(wait_ast),
+            Ok(_) if rule == Rule::PIN => Some(Directive::Pin),
+            Ok(_) => Some(Directive::Silent),
+            Err(_) => None
+        };
+        let Some(directive) = directive else {
+            if rule == Rule::WAIT {
+                DisplaySyntaxError::error(
+                    "Invalid wait duration.", 
+                    "Expected a duration like `30s`, `2m` or `1m30s`.", 
+                    &self.file_path, 
+                    line, 
+                    line_number, 
+                    0
+                );
+            } else {
+                DisplaySyntaxError::error(
+                    "Unexpected text after the directive.", 
+                    "Expected the directive alone in the line.", 
+                    &self.file_path, 
+                    line, 
+                    line_number, 
+                    0
+                );
+            }
+            self.exit();
+            return;
+        };
+        self.mcqps.push(Mcqp {
+            _type: McqpType::Directive,
+            poll: None,
+            question: None,
+            message: None,
+            directive: Some(directive),
+            media: None,
+            album: None,
+            number: 0,
+            meta: meta_parser::Meta::new()
+        });
+    }
+
+    /// Return the number of the sections without the directives.
+    fn sections_len(&self) -> usize {
+        return self.mcqps
+            .iter()
+            .filter( |mcqp| mcqp._type != McqpType::Directive )
+            .count();
+    }
+
+    /// The section metadata parser.
+    fn parse_meta(&mut self, meta_ast: pest::iterators::Pairs<'_, Rule>, line: &str, line_number: usize) {
+        self.next_meta.parse(meta_ast);
+        self.next_meta_line = (line.to_string(), line_number);
+        if let Some(id) = &self.next_meta.id {
+            if id.is_empty() {
+                DisplaySyntaxError::error(
+                    "The section id is empty.", 
+                    "Expected a section id, found None.", 
+                    &self.file_path, 
+                    line, 
+                    line_number, 
+                    line.chars().count()
+                );
+                DisplaySyntaxError::fix_add(
+                    "Add any unique id to the section.", 
+                    line, 
+                    " section-01", 
+                    line_number, 
+                    line.chars().count()
+                );
+                self.exit();
+            }
+            if self.mcqps.iter().any( |mcqp| mcqp.meta.id.as_ref() == Some(id) ) {
+                DisplaySyntaxError::error(
+                    "The section id is already used.", 
+                    "Expected a unique id, found an id used by a previous section.", 
+                    &self.file_path, 
+                    line, 
+                    line_number, 
+                    0
+                );
+                self.exit();
+            }
+        }
+    }
+
+    /// Display the markup error and its fix, then exit. The position is 
+    /// the char position of the error in the line.
+    fn markup_error(&self, err: &MarkupError, line: &str, line_number: usize, position: usize) {
+        DisplaySyntaxError::error(
+            &err.message, 
+            &err.hint, 
+            &self.file_path, 
+            line,
+            line_number, 
+            position
+        );
+        match err.fix {
+            MarkupFix::Insert(add) => DisplaySyntaxError::fix_add(
+                &err.fix_message(), 
+                line, 
+                add, 
+                line_number, 
+                position
+            ),
+            MarkupFix::Replace(replace) => DisplaySyntaxError::fix_replace(
+                &err.fix_message(), 
+                line, 
+                replace, 
+                line_number, 
+                position
+            )
+        }
+        self.exit();
+    }
+
+    /// Check the markup of the section text, the text is searched in the line after 
+    /// the `from` byte to point to the error. The errors are shown only if `md_err` is set, 
+    /// otherwise the text is fixed later.
+    fn check_markup(&self, text: &str, parse_mode: ParseMode, line: &str, line_number: usize, from: usize) {
+        if !self.config.md_err {
+            return;
+        }
+        if let Err(err) = markup::parse(text, parse_mode) {
+            let start = line[from..]
+                .find(text)
+                .map( |index| line[..from + index].chars().count() )
+                .unwrap_or(0);
+            self.markup_error(&err, line, line_number, start + err.offset);
+        }
+    }
+
+    /// Warn if the poll/question text has formatting that Telegram does NOT show, 
+    /// the question and the options show only the custom emoji.
+    fn check_poll_entities(&self, text: &str, parse_mode: ParseMode, line_number: usize) {
+        let Ok(formatted) = markup::parse(text, parse_mode) else { return; };
+        if let Some(entity) = formatted.entities.iter().find( |entity| entity.kind != EntityKind::CustomEmoji ) {
+            Log::new("parser").warn(&format!(
+                "The line {} has {} formatting, Telegram shows only the custom emoji in the poll question and options.",
+                line_number,
+                entity.kind.name()
+            ));
+        }
+    }
+
+    /// Return the metadata of the next section and reset it.
+    fn take_meta(&mut self) -> meta_parser::Meta {
+        return std::mem::replace(&mut self.next_meta, meta_parser::Meta::new());
+    }
+
+    /// Return the content hash of all sections and the changed config.
+    pub fn hash(&self) -> String {
+        let mut content = self.mcqps
+            .iter()
+            .map( |section| section.hash() )
+            .collect::<Vec<String>>()
+            .join("\n");
+        // The default config is not added, so the old hashes do not change.
+        let config = self.config.changed();
+        if !config.is_empty() {
+            content += &format!("\n{}", config.join(", "));
+        }
+        return utils::hash(&content);
+    }
+
+    /// Keep only the sections that match the predicate, the sections counts will be updated.
+    /// The `wait:` directives are always kept, and the `pin:`/`silent:` directives are 
+    /// kept only if the next section is kept.
+    pub fn retain<F: FnMut(&Mcqp) -> bool>(&mut self, mut f: F) {
+        let mut is_next_kept = true;
+        let mut keep = self.mcqps
+            .iter()
+            .rev()
+            .map( |mcqp| match &mcqp.directive {
+                Some(Directive::Wait(_)) => true,
+                Some(_) => is_next_kept,
+                None => {
+                    is_next_kept = f(mcqp);
+                    is_next_kept
+                }
+            })
+            .collect::<Vec<bool>>();
+        keep.reverse();
+        let mut keep = keep.into_iter();
+        self.mcqps.retain( |_| keep.next().unwrap_or(false) );
+        self.poll_count = self.mcqps
+            .iter()
+            .filter( |mcqp| mcqp._type == McqpType::Poll || mcqp._type == McqpType::MCPoll )
+            .count() as u16;
+        self.question_count = self.mcqps
+            .iter()
+            .filter( |mcqp| mcqp._type == McqpType::Question )
+            .count() as u16;
+        self.message_count = self.mcqps
+            .iter()
+            .filter( |mcqp| mcqp._type == McqpType::Message )
+            .count() as u16;
+        self.media_count = self.mcqps
+            .iter()
+            .filter( |mcqp| mcqp._type == McqpType::Media || mcqp._type == McqpType::Album )
+            .count() as u16;
+    }
+
+    /// Exit the program with `can not parse the file` error, or count the error if `exit_on_error` is off.
     fn exit(&self) {
+        if !self.exit_on_error {
+            self.errors.set(self.errors.get() + 1);
+            return;
+        }
         let logger = Log::new("parser");
         logger.error("Can not parse the file!");
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Parse the example file of the directives.
+    fn directives_example() -> McqpAST {
+        return try_parse_file("example/directives.mcq", std::path::Path::new("example")).unwrap();
+    }
+
+    /// Return the type names of the sections.
+    fn types(abstraction_tree: &McqpAST) -> Vec<String> {
+        return abstraction_tree.mcqps
+            .iter()
+            .map( |section| match &section.directive {
+                Some(directive) => directive.text(),
+                None => section._type.name().to_string()
+            })
+            .collect();
+    }
+
+    #[test]
+    fn retain_keeps_the_waits_and_the_directives_of_the_kept_sections() {
+        let mut abstraction_tree = directives_example();
+        abstraction_tree.retain( |section| section._type == McqpType::Question );
+        assert_eq!(types(&abstraction_tree), vec!["wait: 30s", "silent:", "question", "wait: 60s"]);
+        assert_eq!(abstraction_tree.question_count, 1);
+        assert_eq!(abstraction_tree.message_count, 0);
+    }
+
+    #[test]
+    fn retain_all() {
+        let mut abstraction_tree = directives_example();
+        let before = types(&abstraction_tree);
+        abstraction_tree.retain( |_| true );
+        assert_eq!(types(&abstraction_tree), before);
+        assert_eq!(abstraction_tree.message_count, 2);
+    }
+
+    #[test]
+    fn retain_none() {
+        let mut abstraction_tree = directives_example();
+        abstraction_tree.retain( |_| false );
+        assert_eq!(types(&abstraction_tree), vec!["wait: 30s", "wait: 60s"]);
+        assert_eq!(abstraction_tree.question_count + abstraction_tree.message_count, 0);
+    }
+}
@@ -3,19 +3,27 @@
 
 use pest::iterators::Pairs;
 use super::Rule;
+use crate::markup::{self, ParseMode};
+use crate::limits;
 
 pub struct Poll {
     /// The poll question
     question: String,
     /// The poll choices
-    choices: Vec<String>
+    choices: Vec<String>,
+    /// The poll is anonymous
+    is_anonymous: bool,
+    /// The parse mode of the question and choices, it is set by the `poll_parse_mode` config.
+    parse_mode: ParseMode
 }
 
 impl Poll {
     pub fn new() -> Self {
         return Self {
             question: String::new(),
-            choices: Vec::new()
+            choices: Vec::new(),
+            is_anonymous: true,
+            parse_mode: ParseMode::None
         }
     }
 
@@ -24,6 +32,53 @@ impl Poll {
         return self.question.clone();
     }
 
+    /// Return the question length as Telegram measures it.
+    pub fn question_len(&self) -> usize {
+        return limits::len(&self.question, self.parse_mode);
+    }
+
+    /// Return `true` if the poll is anonymous.
+    pub fn is_anonymous(&self) -> bool {
+        return self.is_anonymous;
+    }
+
+    /// Set the poll anonymity, it is set by the `anonymous` config.
+    pub fn set_anonymous(&mut self, is_anonymous: bool) {
+        self.is_anonymous = is_anonymous;
+    }
+
+    /// Return the question plain text, it is the text that the users see.
+    pub fn plain_question(&self) -> String {
+        return markup::plain(&self.question, self.parse_mode);
+    }
+
+    /// Return the choices plain text.
+    pub fn plain_choices(&self) -> Vec<String> {
+        return self.choices
+            .iter()
+            .map( |choice| markup::plain(choice, self.parse_mode) )
+            .collect();
+    }
+
+    /// Return the parse mode.
+    pub fn parse_mode(&self) -> ParseMode {
+        return self.parse_mode;
+    }
+
+    /// Set the parse mode, it must be set before parsing the poll.
+    pub fn set_parse_mode(&mut self, parse_mode: ParseMode) {
+        self.parse_mode = parse_mode;
+    }
+
+    /// Fix the markup errors by escaping the offending chars, it is used if `md_err` is NOT set.
+    pub fn fix_markup(&mut self) {
+        self.question = markup::fix(&self.question, self.parse_mode);
+        self.choices = self.choices
+            .iter()
+            .map( |choice| markup::fix(choice, self.parse_mode) )
+            .collect();
+    }
+
     /// Return a cloned choices.
     pub fn choices(&self) -> Vec<String> {
         return self.choices.clone();
@@ -69,10 +124,7 @@ impl Poll {
     /// Check if last option in the opctions list is valid.
     pub fn is_last_option_valid(&self) -> bool {
         if let Some(option) = self.choices.last() {
-            let len = option.chars().count();
-            if len >= 1 && len <= 100 {
-                return true;
-            }
+            return limits::OPTION.is_valid(limits::len(option, self.parse_mode));
         }
         return false;
     }
@@ -87,18 +139,14 @@ impl Poll {
 
     /// Check the poll question length.
     pub fn is_question_valid(&self) -> bool {
-        let question_len = self.question.chars().count();
-        if question_len < 1 || question_len > 255 {
-            return false;
-        }
-        return true;
+        return limits::QUESTION.is_valid(self.question_len());
     }
 
     /// Add a counter to the question. It will return `false` if 
     /// the question length so big.
     pub fn add_count(&mut self, counter: usize) -> bool {
-        let new_question = format!("{}.{}", counter, self.question);
-        if new_question.chars().count() > 255 {
+        let new_question = format!("{}{}", self.parse_mode.escape(&format!("{}.", counter)), self.question);
+        if limits::len(&new_question, self.parse_mode) > limits::QUESTION.max {
             return false;
         }
         self.question = new_question;
@@ -3,6 +3,9 @@
 
 use pest::iterators::Pairs;
 use super::Rule;
+use crate::markup::{self, ParseMode};
+use crate::limits;
+use crate::utils;
 
 pub struct Question {
     /// The question string
@@ -13,6 +16,14 @@ pub struct Question {
     answer: i8,
     /// The question note
     note: Option<String>,
+    /// The question is anonymous
+    is_anonymous: bool,
+    /// The parse mode of the question, choices and note, it is set by the `poll_parse_mode` config.
+    parse_mode: ParseMode,
+    /// The question number, the quiz refers to the overflow message by it.
+    number: usize,
+    /// The length of the counter in the start of the question (`12.`).
+    counter_len: usize
 }
 
 
@@ -22,7 +33,11 @@ impl Question {
             question: String::new(),
             choices: Vec::new(),
             answer: -1,
-            note: None
+            note: None,
+            is_anonymous: true,
+            parse_mode: ParseMode::None,
+            number: 0,
+            counter_len: 0
         };
     }
 
@@ -31,9 +46,73 @@ impl Question {
         return self.question.clone();
     }
 
-    /// Return the question length.
+    /// Return the stable content hash of the question, the counter is NOT hashed, 
+    /// so the hash does not change when the questions before it are changed.
+    pub fn content_hash(&self) -> String {
+        return utils::hash(&format!(
+            "{}\n{}\n{}\n{}\n",
+            &self.question[self.counter_len..],
+            self.choices.join("\n"),
+            self.answer,
+            self.note.clone().unwrap_or_default()
+        ));
+    }
+
+    /// Return the question length as Telegram measures it.
     pub fn question_len(&self) -> usize {
-        return self.question.chars().count();
+        return limits::len(&self.question, self.parse_mode);
+    }
+
+    /// Return `true` if the question is anonymous.
+    pub fn is_anonymous(&self) -> bool {
+        return self.is_anonymous;
+    }
+
+    /// Set the question anonymity, it is set by the `anonymous` config.
+    pub fn set_anonymous(&mut self, is_anonymous: bool) {
+        self.is_anonymous = is_anonymous;
+    }
+
+    /// Return the question plain text, it is the text that the users see.
+    pub fn plain_question(&self) -> String {
+        return markup::plain(&self.question, self.parse_mode);
+    }
+
+    /// Return the choices plain text.
+    pub fn plain_choices(&self) -> Vec<String> {
+        return self.choices
+            .iter()
+            .map( |choice| markup::plain(choice, self.parse_mode) )
+            .collect();
+    }
+
+    /// Return the note plain text.
+    pub fn plain_note(&self) -> Option<String> {
+        return self.note
+            .as_ref()
+            .map( |note| markup::plain(note, self.parse_mode) );
+    }
+
+    /// Return the parse mode.
+    pub fn parse_mode(&self) -> ParseMode {
+        return self.parse_mode;
+    }
+
+    /// Set the parse mode, it must be set before parsing the question.
+    pub fn set_parse_mode(&mut self, parse_mode: ParseMode) {
+        self.parse_mode = parse_mode;
+    }
+
+    /// Fix the markup errors by escaping the offending chars, it is used if `md_err` is NOT set.
+    pub fn fix_markup(&mut self) {
+        self.question = markup::fix(&self.question, self.parse_mode);
+        self.choices = self.choices
+            .iter()
+            .map( |choice| markup::fix(choice, self.parse_mode) )
+            .collect();
+        self.note = self.note
+            .as_ref()
+            .map( |note| markup::fix(note, self.parse_mode) );
     }
 
     /// Return a cloned choices.
@@ -103,10 +182,7 @@ impl Question {
     /// Check if last option in the opctions list is valid.
     pub fn is_last_option_valid(&self) -> bool {
         if let Some(option) = self.choices.last() {
-            let len = option.chars().count();
-            if len >= 1 && len <= 100 {
-                return true;
-            }
+            return limits::OPTION.is_valid(limits::len(option, self.parse_mode));
         }
         return false;
     }
@@ -121,11 +197,7 @@ impl Question {
 
     /// Check the length of the question.
     pub fn is_question_valid(&self) -> bool {
-        let question_len = self.question_len();
-        if question_len < 1 || question_len > 255 {
-            return false;
-        }
-        return true;
+        return limits::QUESTION.is_valid(self.question_len());
     }
 
     /// Check if there is note.
@@ -139,21 +211,86 @@ impl Question {
     /// Check the note length.
     pub fn is_note_valid(&self) -> bool {
         if let Some(note) = &self.note {
-            let note_len = note.chars().count();
-            if note_len < 1 || note_len > 200 {
-                return false;
-            }
+            return limits::NOTE.is_valid(limits::len(note, self.parse_mode));
         }
         return true;
     }
 
+    /// Set the question number, it is the counter number if the `counter` config is set.
+    pub fn set_number(&mut self, number: usize) {
+        self.number = number;
+    }
+
+    /// Check if the question is more than the Telegram limit, it can be 
+    /// sent in the overflow message if the `overflow` config is set.
+    pub fn is_question_overflow(&self) -> bool {
+        return self.question_len() > limits::QUESTION.max;
+    }
+
+    /// Check if the last option is more than the Telegram limit.
+    pub fn is_last_option_overflow(&self) -> bool {
+        return self.choices
+            .last()
+            .is_some_and( |option| limits::len(option, self.parse_mode) > limits::OPTION.max );
+    }
+
+    /// Check if any option is more than the Telegram limit.
+    pub fn is_options_overflow(&self) -> bool {
+        return self.choices
+            .iter()
+            .any( |option| limits::len(option, self.parse_mode) > limits::OPTION.max );
+    }
+
+    /// Check if the question needs the overflow message.
+    pub fn is_overflow(&self) -> bool {
+        return self.is_question_overflow() || self.is_options_overflow();
+    }
+
+    /// Return the overflow message that is sent before the quiz, it has the long 
+    /// question and the lettered options if any option is long.
+    pub fn overflow_message(&self) -> String {
+        let mut message = if self.is_question_overflow() {
+            format!("{} {}", self.parse_mode.escape(&format!("Q{}.", self.number)), self.question)
+        } else {
+            self.question.clone()
+        };
+        if self.is_options_overflow() {
+            message.push('\n');
+            self.choices.iter().enumerate().for_each( |(index, choice)| {
+                let letter = format!("{}.", (b'A' + index as u8) as char);
+                message += &format!("\n{} {}", self.parse_mode.escape(&letter), choice);
+            });
+        }
+        return message;
+    }
+
+    /// Return the question of the quiz, it refers to the overflow message if the question is long.
+    pub fn quiz_question(&self) -> String {
+        if self.is_question_overflow() {
+            return self.parse_mode.escape(&format!("Q{} (see above)", self.number));
+        }
+        return self.question.clone();
+    }
+
+    /// Return the options of the quiz, they are the letters of the 
+    /// overflow message options if any option is long.
+    pub fn quiz_choices(&self) -> Vec<String> {
+        if self.is_options_overflow() {
+            return (0..self.choices.len())
+                .map( |index| ((b'A' + index as u8) as char).to_string() )
+                .collect();
+        }
+        return self.choices.clone();
+    }
+
     /// Add a counter to the question. It will return `false` if 
     /// the question length so big.
     pub fn add_count(&mut self, counter: usize) -> bool {
-        let new_question = format!("{}.{}", counter, self.question);
-        if new_question.chars().count() > 255 {
+        let new_question = format!("{}{}", self.parse_mode.escape(&format!("{}.", counter)), self.question);
+        if limits::len(&new_question, self.parse_mode) > limits::QUESTION.max {
             return false;
         }
+        self.counter_len = new_question.len() - self.question.len();
         self.question = new_question;
         return true;
     }
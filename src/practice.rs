@@ -0,0 +1,184 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use std::io::Read;
+use clap::ArgMatches;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::file;
+use crate::filter::Filter;
+use crate::log::Log;
+use crate::parser::{self, Question};
+use crate::utils;
+
+/// The practice progress of a file, it is saved in the data dir
+/// after every practice, so the wrong questions can be practiced again.
+#[derive(Serialize, Deserialize, Default)]
+struct Progress {
+    /// The content hashes (`Question::content_hash`) of the questions that were 
+    /// answered wrong, they do not change when the counter of the question changes.
+    wrong: Vec<String>
+}
+
+impl Progress {
+    /// Read the practice progress, it returns the default if there is no saved progress.
+    fn load(path: &std::path::PathBuf) -> Self {
+        let logger = Log::new("practice");
+        if !path.exists() {
+            return Self::default();
+        }
+        let mut file = std::fs::File::open(path)
+            .unwrap_or_else(|_| logger.error("Can NOT open the practice progress file!"));
+        let mut progress_buf = String::new();
+        file
+            .read_to_string(&mut progress_buf)
+            .unwrap_or_else(|_| logger.error("Can NOT read the practice progress file!"));
+        return serde_json::from_str(&progress_buf)
+            .unwrap_or_else(|_| logger.error(&format!("Can NOT parse the practice progress ({})!", path.display())));
+    }
+
+    /// Save the practice progress.
+    fn save(&self, path: &std::path::Path) {
+        let logger = Log::new("practice");
+        let json = serde_json::to_string(&self)
+            .unwrap_or_else(|_| logger.error("Can NOT make json object!"));
+        utils::write_file(path, &json)
+            .unwrap_or_else(|_| logger.error("Can NOT write to the practice progress file!"));
+    }
+
+    /// Add the question hash to the wrong questions, or remove it if it was answered right.
+    fn update(&mut self, hash: &str, is_right: bool) {
+        self.wrong.retain( |wrong| wrong != hash );
+        if !is_right {
+            self.wrong.push(hash.to_owned());
+        }
+    }
+}
+
+/// A small xorshift random generator, it is seeded by the current time.
+/// It is not secure, but it is enough to shuffle the questions.
+struct Shuffler {
+    state: u64
+}
+
+impl Shuffler {
+    fn new() -> Self {
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map( |duration| duration.as_nanos() as u64 )
+            .unwrap_or(0);
+        // The state must not be zero.
+        return Self { state: nanos | 1 };
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        return self.state;
+    }
+
+    /// Shuffle the items with the Fisher-Yates shuffle.
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = (self.next() % (i as u64 + 1)) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+/// Ask the question in the terminal, it returns `None` if the user wants to quit.
+pub fn ask(question: &Question, order: &[usize], number: usize, total: usize) -> Option<bool> {
+    let choices = question.plain_choices();
+    let answer = question.answer() as usize;
+    let last_letter = (b'A' + order.len() as u8 - 1) as char;
+    println!("\n{} {}", format!("[{}/{}]", number, total).cyan(), question.plain_question().bold());
+    order.iter().enumerate().for_each( |(letter, &choice)| {
+        println!("  {}. {}", (b'A' + letter as u8) as char, choices[choice]);
+    });
+    let selected = loop {
+        let input = utils::input(&format!("Your answer (A-{}, Enter to skip, q to quit): ", last_letter));
+        match input.to_uppercase().as_str() {
+            "Q" => return None,
+            "" => break None,
+            letter if letter.len() == 1 => {
+                let index = (letter.as_bytes()[0] as usize).wrapping_sub(b'A' as usize);
+                if index < order.len() { break Some(order[index]); }
+            },
+            _ => {}
+        }
+        println!("{}", format!("Please enter a letter between A and {}.", last_letter).yellow());
+    };
+    let is_right = selected == Some(answer);
+    if is_right {
+        println!("{}", "✅ Right!".green());
+    } else {
+        let letter = order.iter().position( |&choice| choice == answer ).unwrap_or(0);
+        println!(
+            "{} {}. {}",
+            "❌ Wrong, the answer is".red(),
+            (b'A' + letter as u8) as char,
+            choices[answer]
+        );
+    }
+    if let Some(note) = question.plain_note() {
+        println!("💡 {}", note);
+    }
+    return Some(is_right);
+}
+
+/// Take the quiz in the terminal.
+pub fn main(command: &ArgMatches) {
+    let logger = Log::new("practice");
+    let file = command.get_one::<String>("FILE").unwrap();
+    let mut abstraction_tree = parser::parse_file(file, &logger);
+    Filter::new(command).apply(&mut abstraction_tree);
+    let progress_path = utils::data_path(
+        &format!("practice/{}.json", utils::hash(&file::absolute(file)))
+    );
+    let mut progress = Progress::load(&progress_path);
+    let mut questions = abstraction_tree.mcqps
+        .iter()
+        .filter_map( |section| section.question.as_ref().map( |question| (question.content_hash(), question) ) )
+        .collect::<Vec<(String, &Question)>>();
+    if command.get_flag("wrong-only") {
+        questions.retain( |(hash, _)| progress.wrong.contains(hash) );
+        if questions.is_empty() {
+            logger.error("There are no wrong answered questions to practice!");
+        }
+    }
+    if questions.is_empty() {
+        logger.error("There are no questions to practice in the file!");
+    }
+    let is_shuffle = command.get_flag("shuffle");
+    let mut shuffler = Shuffler::new();
+    if is_shuffle {
+        shuffler.shuffle(&mut questions);
+    }
+    let total = questions.len();
+    let mut score = 0;
+    let mut answered = 0;
+    let mut missed = Vec::new();
+    for (index, (hash, question)) in questions.iter().enumerate() {
+        let mut order = (0..question.choices().len()).collect::<Vec<usize>>();
+        if is_shuffle {
+            shuffler.shuffle(&mut order);
+        }
+        let Some(is_right) = ask(question, &order, index + 1, total) else { break; };
+        answered += 1;
+        if is_right {
+            score += 1;
+        } else {
+            missed.push(question.plain_question());
+        }
+        progress.update(hash, is_right);
+    }
+    progress.save(&progress_path);
+    println!("\n{} {}/{}", "Your score:".bold(), score.to_string().green(), answered);
+    if !missed.is_empty() {
+        println!("{}", "The missed questions:".bold());
+        missed.iter().for_each( |question| println!("  - {}", question.red()) );
+        println!("Run it again with `--wrong-only` to practice the missed questions only.");
+    }
+}
@@ -0,0 +1,342 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use std::collections::HashSet;
+use std::io::Read;
+use clap::ArgMatches;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::config;
+use crate::ledger::{Ledger, Record};
+use crate::log::Log;
+use crate::telegram::{self, Telegram};
+use crate::utils;
+
+/// The saved updates file name in the data dir.
+const UPDATES_FILE: &str = "updates.json";
+
+/// The bot update, the `poll_answer` updates are used for the results and the 
+/// `message` updates are kept for `mcqp config` (the chats of the bot).
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Update {
+    /// The update id.
+    pub update_id: i64,
+    /// The user answer of a non-anonymous poll.
+    pub poll_answer: Option<PollAnswer>,
+    /// The message that was sent to the bot.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<serde_json::Value>,
+    /// The time that mcqp received the update (unix time), telegram 
+    /// does not send the answer time, so this is the closest to it.
+    #[serde(default)]
+    pub received_at: Option<u64>
+}
+
+/// The user answer of a non-anonymous poll.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PollAnswer {
+    /// The poll id.
+    pub poll_id: String,
+    /// The user that answered the poll.
+    pub user: Option<User>,
+    /// The chosen options, it is empty if the user retracted the vote.
+    pub option_ids: Vec<usize>
+}
+
+/// The telegram user.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct User {
+    pub id: i64,
+    pub first_name: String,
+    pub last_name: Option<String>,
+    pub username: Option<String>
+}
+
+/// The getUpdates dump, it can be the API response or the updates list.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum UpdatesDump {
+    Response { result: Vec<Update> },
+    Updates(Vec<Update>)
+}
+
+/// The user answer of a question.
+#[derive(Serialize)]
+struct Answer {
+    /// The section number in the file.
+    section: usize,
+    /// The question text.
+    question: String,
+    /// The chosen option index.
+    option: usize,
+    /// The answer is correct.
+    correct: bool,
+    /// The time that mcqp received the answer.
+    answered_at: Option<String>
+}
+
+/// The user score.
+#[derive(Serialize)]
+struct Score {
+    user_id: i64,
+    name: String,
+    username: String,
+    /// The number of correct answers.
+    score: usize,
+    /// The number of answered questions.
+    answered: usize,
+    /// The number of questions.
+    total: usize,
+    answers: Vec<Answer>
+}
+
+/// Read the saved updates from the data dir.
+fn load_updates() -> Vec<Update> {
+    let logger = Log::new("results");
+    let updates_file = utils::data_path(UPDATES_FILE);
+    if !updates_file.exists() {
+        return Vec::new();
+    }
+    return read_updates(&updates_file.display().to_string())
+        .unwrap_or_else(|| logger.error("Can NOT parse the saved updates!"));
+}
+
+/// Save the updates to the data dir, because the fetched updates are 
+/// removed from telegram.
+fn save_updates(updates: &Vec<Update>) {
+    let logger = Log::new("results");
+    let json = serde_json::to_string(updates)
+        .unwrap_or_else(|_| logger.error("Can NOT make json object!"));
+    utils::write_file(&utils::data_path(UPDATES_FILE), &json)
+        .unwrap_or_else(|_| logger.error("Can NOT write to the updates file!"));
+}
+
+/// Keep only the updates that are still used, the poll answers of the polls in 
+/// the ledger and the last message of every chat. The last update is always kept, 
+/// so the next fetch starts after it.
+fn prune_updates(updates: Vec<Update>) -> Vec<Update> {
+    let ledger = Ledger::load();
+    let poll_ids = ledger.runs
+        .iter()
+        .flat_map( |run| run.records.iter() )
+        .filter_map( |record| record.poll_id.as_deref() )
+        .collect::<HashSet<&str>>();
+    let last_update_id = updates.last().map( |update| update.update_id );
+    let mut chats = HashSet::new();
+    let mut kept = updates
+        .into_iter()
+        .rev()
+        .filter( |update| {
+            let is_used = match (&update.poll_answer, &update.message) {
+                (Some(poll_answer), _) => poll_ids.contains(poll_answer.poll_id.as_str()),
+                (None, Some(message)) => chats.insert(message["chat"]["id"].to_string()),
+                (None, None) => false
+            };
+            return is_used || Some(update.update_id) == last_update_id;
+        })
+        .collect::<Vec<Update>>();
+    kept.reverse();
+    return kept;
+}
+
+/// Return the saved messages that were sent to the bot, they are fetched 
+/// with the poll answers so they are removed from telegram.
+pub fn saved_messages() -> Vec<serde_json::Value> {
+    return load_updates()
+        .into_iter()
+        .filter_map( |update| update.message )
+        .collect();
+}
+
+/// Read the updates from a getUpdates dump file.
+fn read_updates(path: &str) -> Option<Vec<Update>> {
+    let mut file = std::fs::File::open(path).ok()?;
+    let mut updates_buf = String::new();
+    file.read_to_string(&mut updates_buf).ok()?;
+    return match serde_json::from_str::<UpdatesDump>(&updates_buf).ok()? {
+        UpdatesDump::Response { result } => Some(result),
+        UpdatesDump::Updates(updates) => Some(updates)
+    };
+}
+
+/// Fetch the new `poll_answer` and `message` updates using long polling, until 
+/// there is no new updates. The fetched updates are added to the saved updates and 
+/// the unused saved updates are pruned.
+pub async fn fetch_updates(telegram: &Telegram, timeout: u64) -> Vec<Update> {
+    let logger = Log::new("results");
+    let mut updates = load_updates();
+    let mut offset = updates.last().map( |update| update.update_id + 1 ).unwrap_or(0);
+    loop {
+        let new_updates = telegram.request::<_, Vec<Update>>("getUpdates", &json!({
+            "offset": offset,
+            "timeout": timeout,
+            "allowed_updates": telegram::ALLOWED_UPDATES
+        })).await.unwrap_or_else(|err| logger.error(&format!("Can NOT get the updates! {}", err)));
+        if new_updates.is_empty() {
+            break;
+        }
+        offset = new_updates.last().unwrap().update_id + 1;
+        updates.extend(
+            new_updates
+                .into_iter()
+                .filter( |update| update.poll_answer.is_some() || update.message.is_some() )
+                .map( |mut update| {
+                    update.received_at = Some(utils::now());
+                    update
+                })
+        );
+        save_updates(&updates);
+    }
+    let updates = prune_updates(updates);
+    save_updates(&updates);
+    // Confirm the last update, so telegram will not send it again.
+    let _ = telegram.request::<_, Vec<Update>>("getUpdates", &json!({
+        "offset": offset,
+        "timeout": 0,
+        "allowed_updates": telegram::ALLOWED_UPDATES
+    })).await;
+    return updates;
+}
+
+/// Grade the users answers of the questions records.
+fn grade(questions: &Vec<&Record>, updates: &Vec<Update>) -> Vec<Score> {
+    let mut scores: Vec<Score> = Vec::new();
+    for update in updates {
+        let Some(poll_answer) = &update.poll_answer else { continue; };
+        let Some(user) = &poll_answer.user else { continue; };
+        let Some(record) = questions
+            .iter()
+            .find( |record| record.poll_id.as_deref() == Some(&poll_answer.poll_id) ) else { continue; };
+        let score_index = match scores.iter().position( |score| score.user_id == user.id ) {
+            Some(index) => index,
+            None => {
+                scores.push(Score {
+                    user_id: user.id,
+                    name: format!("{} {}", user.first_name, user.last_name.clone().unwrap_or_default())
+                        .trim()
+                        .to_string(),
+                    username: user.username.clone().unwrap_or_default(),
+                    score: 0,
+                    answered: 0,
+                    total: questions.len(),
+                    answers: Vec::new()
+                });
+                scores.len() - 1
+            }
+        };
+        let score = &mut scores[score_index];
+        // The last answer of the poll is the user answer.
+        score.answers.retain( |answer| answer.section != record.section );
+        if let Some(&option) = poll_answer.option_ids.first() {
+            score.answers.push(Answer {
+                section: record.section,
+                question: record.question.clone().unwrap_or_default(),
+                option,
+                correct: record.answer == Some(option as i8),
+                answered_at: update.received_at.map(utils::format_time)
+            });
+        }
+        score.answered = score.answers.len();
+        score.score = score.answers.iter().filter( |answer| answer.correct ).count();
+    }
+    scores.iter_mut().for_each( |score| score.answers.sort_by_key( |answer| answer.section ) );
+    scores.sort_by_key( |score| std::cmp::Reverse(score.score) );
+    return scores;
+}
+
+/// Make the CSV scores table, a row for every user and a column for every question.
+fn to_csv(questions: &Vec<&Record>, scores: &Vec<Score>) -> String {
+    let escape = |field: &str| -> String {
+        if field.contains(',') || field.contains('"') || field.contains('\n') {
+            return format!("\"{}\"", field.replace('"', "\"\""));
+        }
+        return field.to_string();
+    };
+    let mut csv = String::from("user_id,name,username,score,answered,total");
+    questions.iter().for_each( |record| csv += &format!(",Q{}", record.section) );
+    csv += ",last_answer_at\n";
+    for score in scores {
+        csv += &format!(
+            "{},{},{},{},{},{}", 
+            score.user_id, 
+            escape(&score.name), 
+            escape(&score.username), 
+            score.score, 
+            score.answered, 
+            score.total
+        );
+        for record in questions {
+            csv += match score.answers.iter().find( |answer| answer.section == record.section ) {
+                Some(answer) if answer.correct => ",1",
+                Some(_) => ",0",
+                None => ","
+            };
+        }
+        csv += &format!(
+            ",{}\n", 
+            score.answers
+                .iter()
+                .filter_map( |answer| answer.answered_at.clone() )
+                .max()
+                .unwrap_or_default()
+        );
+    }
+    return csv;
+}
+
+/// Collect the quiz results of a send run and grade the users.
+pub async fn main(command: &ArgMatches) {
+    let logger = Log::new("results");
+    let ledger = Ledger::load();
+    let run = match command.get_one::<String>("run") {
+        Some(id) => {
+            let id = id.parse::<u64>().unwrap_or_else(|_| logger.error("The run id must be a number!"));
+            ledger.runs
+                .iter()
+                .find( |run| run.id == id )
+                .unwrap_or_else(|| logger.error(&format!("The run #{} is NOT found!", id)))
+        },
+        None => ledger.runs
+            .iter()
+            .rev()
+            .find( |run| !run.records.is_empty() )
+            .unwrap_or_else(|| logger.error("There is no sent sections!"))
+    };
+    let questions = run.records
+        .iter()
+        .filter( |record| record.answer.is_some() && record.poll_id.is_some() )
+        .collect::<Vec<&Record>>();
+    if questions.is_empty() {
+        logger.error(&format!("There is no questions in the run #{}!", run.id));
+    }
+    let updates = match command.get_one::<String>("updates") {
+        Some(path) => read_updates(path).unwrap_or_else(|| logger.error("Can NOT read the updates file!")),
+        None => {
+            let mut send_config = config::Config::new();
+            send_config.get_config();
+            let timeout = command
+                .get_one::<String>("timeout")
+                .map( |timeout| timeout.parse::<u64>().unwrap_or_else(|_| logger.error("The timeout must be a number!")) )
+                .unwrap_or(5);
+            fetch_updates(&Telegram::new(&send_config.bot_token), timeout).await
+        }
+    };
+    let scores = grade(&questions, &updates);
+    let output = match command.get_one::<String>("format").map( |format| format.as_str() ) {
+        Some("json") => serde_json::to_string_pretty(&scores)
+            .unwrap_or_else(|_| logger.error("Can NOT make json object!")),
+        _ => to_csv(&questions, &scores)
+    };
+    match command.get_one::<String>("output") {
+        Some(path) => {
+            std::fs::write(path, output)
+                .unwrap_or_else(|_| logger.error("Can NOT write the output file!"));
+            if scores.is_empty() {
+                logger.info("There is no answers yet, the questions must be sent with `anonymous = false`.");
+            }
+            logger.info(&format!("The results of {} users saved to {}.", scores.len(), path));
+        },
+        None => print!("{}", output)
+    }
+}
@@ -0,0 +1,222 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use std::io::Read;
+use clap::ArgMatches;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::file;
+use crate::filter::Filter;
+use crate::ledger::Ledger;
+use crate::log::Log;
+use crate::parser;
+use crate::send;
+use crate::utils;
+
+/// The queue file name in the data dir.
+const QUEUE_FILE: &str = "schedule.json";
+
+/// The max time to sleep before reading the queue again, so 
+/// the new queued files are not missed.
+const CHECK_INTERVAL: u64 = 30;
+
+/// The queued files.
+#[derive(Serialize, Deserialize)]
+struct Queue {
+    jobs: Vec<Job>
+}
+
+/// The queued file.
+#[derive(Serialize, Deserialize)]
+struct Job {
+    /// The job id.
+    id: u64,
+    /// The original .mcq file path.
+    file: String,
+    /// The copy of the .mcq file in the data dir.
+    copy: String,
+    /// The send time (unix time).
+    at: u64,
+    /// The sections filter.
+    filter: Filter,
+    /// The send error, the failed job is kept in the queue until it is canceled.
+    #[serde(default)]
+    error: Option<String>
+}
+
+impl Queue {
+    /// Read the queue from the data dir.
+    fn load() -> Self {
+        let logger = Log::new("schedule");
+        let queue_file = utils::data_path(QUEUE_FILE);
+        if !queue_file.exists() {
+            return Self { jobs: Vec::new() };
+        }
+        let mut file = std::fs::File::open(queue_file)
+            .unwrap_or_else(|_| logger.error("Can NOT open the queue file!"));
+        let mut queue_buf = String::new();
+        file
+            .read_to_string(&mut queue_buf)
+            .unwrap_or_else(|_| logger.error("Can NOT read the queue file!"));
+        return serde_json::from_str(&queue_buf)
+            .unwrap_or_else(|_| logger.error("Can NOT parse the queue!"));
+    }
+
+    /// Save the queue to the data dir.
+    fn save(&self) {
+        let logger = Log::new("schedule");
+        let json = serde_json::to_string(&self)
+            .unwrap_or_else(|_| logger.error("Can NOT make json object!"));
+        utils::write_file(&utils::data_path(QUEUE_FILE), &json)
+            .unwrap_or_else(|_| logger.error("Can NOT write to the queue file!"));
+    }
+}
+
+/// Check the file and queue a copy of it.
+fn add(command: &ArgMatches) {
+    let logger = Log::new("schedule");
+    let file = command.get_one::<String>("FILE").unwrap();
+    let at = utils::parse_time(command.get_one::<String>("at").unwrap())
+        .unwrap_or_else(|| logger.error("Invalid `--at` time, expected a date and a time with an offset like `2026-11-02T08:00+03:00` or `2026-11-02T05:00Z`!"));
+    if at <= utils::now() {
+        logger.error("The `--at` time must be in the future!");
+    }
+    let filter = Filter::new(command);
+    let mut abstraction_tree = parser::parse_file(file, &logger);
+    filter.apply(&mut abstraction_tree);
+    if !command.get_flag("force") {
+        let mut send_config = config::Config::new();
+        send_config.get_config();
+        send::check_sent(&mut abstraction_tree, &Ledger::load(), &send_config.chat_id, false);
+    }
+    let mut queue = Queue::load();
+    let id = queue.jobs.iter().map( |job| job.id ).max().unwrap_or(0) + 1;
+    // Copy the file, so the changes after queuing it will not be sent.
+    let copy = utils::data_path(&format!("schedule/{}.mcq", id));
+    std::fs::copy(file, &copy)
+        .unwrap_or_else(|_| logger.error("Can NOT copy the file to the data dir!"));
+    queue.jobs.push(Job {
+        id,
+        file: file::absolute(file),
+        copy: copy.display().to_string(),
+        at,
+        filter,
+        error: None
+    });
+    queue.save();
+    logger.info(
+        &format!(
+            "The file is queued as #{}, it will be sent at {} by `mcqp schedule run`.", 
+            id, 
+            utils::format_time(at)
+        )
+    );
+}
+
+/// Display the queued files.
+fn list() {
+    let logger = Log::new("schedule");
+    let mut queue = Queue::load();
+    if queue.jobs.is_empty() {
+        logger.info("There is no queued files.");
+        return;
+    }
+    queue.jobs.sort_by_key( |job| job.at );
+    for job in &queue.jobs {
+        println!(
+            "{} {} {}",
+            format!("#{}", job.id).cyan(),
+            utils::format_time(job.at).green(),
+            job.file
+        );
+        if let Some(error) = &job.error {
+            println!("    {} {}", "failed:".red(), error.red());
+        }
+    }
+}
+
+/// Remove the queued file.
+fn cancel(command: &ArgMatches) {
+    let logger = Log::new("schedule");
+    let id = command
+        .get_one::<String>("ID")
+        .unwrap()
+        .trim_start_matches('#')
+        .parse::<u64>()
+        .unwrap_or_else(|_| logger.error("The queued file id must be a number!"));
+    let mut queue = Queue::load();
+    let Some(index) = queue.jobs.iter().position( |job| job.id == id ) else {
+        logger.error(&format!("The queued file #{} is NOT found!", id));
+    };
+    let job = queue.jobs.remove(index);
+    let _ = std::fs::remove_file(&job.copy);
+    queue.save();
+    logger.info(&format!("The queued file #{} is canceled.", id));
+}
+
+/// Send the queued files at their times, it keeps running until it is stopped.
+async fn run() {
+    let logger = Log::new("schedule");
+    let mut send_config = config::Config::new();
+    send_config.get_config();
+    logger.info("The scheduler is running, press Ctrl+C to stop it.");
+    loop {
+        let mut queue = Queue::load();
+        let now = utils::now();
+        if let Some(index) = queue.jobs.iter().position( |job| job.at <= now && job.error.is_none() ) {
+            let id = queue.jobs[index].id;
+            // Mark the job first, so it will not be sent twice if the scheduler stopped while sending it.
+            queue.jobs[index].error = Some("The scheduler stopped while sending the file!".to_string());
+            queue.save();
+            logger.info(&format!("Sending the queued file #{}...", id));
+            let job = &queue.jobs[index];
+            // The media paths are relative to the original file, not the copy.
+            let base_dir = std::path::Path::new(&job.file).parent().unwrap_or(std::path::Path::new(""));
+            let send_result = match parser::try_parse_file(&job.copy, base_dir) {
+                Ok(mut abstraction_tree) => {
+                    job.filter.apply(&mut abstraction_tree);
+                    send::send(abstraction_tree, &send_config, &job.file, &mut Ledger::load()).await
+                },
+                Err(err) => Err(err)
+            };
+            // The queue may be changed by other commands while sending.
+            let mut queue = Queue::load();
+            match send_result {
+                Ok(_) => {
+                    if let Some(index) = queue.jobs.iter().position( |job| job.id == id ) {
+                        let job = queue.jobs.remove(index);
+                        let _ = std::fs::remove_file(&job.copy);
+                    }
+                },
+                Err(err) => {
+                    logger.warn(&format!("The queued file #{} is NOT sent! {}", id, err));
+                    if let Some(job) = queue.jobs.iter_mut().find( |job| job.id == id ) {
+                        job.error = Some(err);
+                    }
+                }
+            }
+            queue.save();
+            continue;
+        }
+        let next = queue.jobs
+            .iter()
+            .filter( |job| job.error.is_none() )
+            .map( |job| job.at )
+            .min()
+            .unwrap_or(u64::MAX);
+        let sleep = (next - now).min(CHECK_INTERVAL);
+        tokio::time::sleep(std::time::Duration::from_secs(sleep)).await;
+    }
+}
+
+pub async fn main(command: &ArgMatches) {
+    match command.subcommand() {
+        Some(("add", command)) => add(command),
+        Some(("list", _)) => list(),
+        Some(("cancel", command)) => cancel(command),
+        Some(("run", _)) => run().await,
+        _ => Log::new("schedule").error("Please use `--help` from the help message!")
+    }
+}
@@ -2,20 +2,120 @@
 // See the LICENSE file for full license details.
 
 use clap::ArgMatches;
-use reqwest::Client;
+use reqwest::multipart::{Form, Part};
 use serde::Serialize;
+use serde_json::json;
 
 use crate::file;
 use crate::parser;
 use crate::log::Log;
+use crate::markup::ParseMode;
 use crate::config;
+use crate::filter::Filter;
+use crate::ledger::{Ledger, Record};
+use crate::summary::{self, ClosedPoll};
+use crate::telegram::{SentMessage, Telegram};
+use crate::utils;
 
 /// The send message DTO
 #[derive(Serialize)]
 struct MessageDto {
     text: String,
     chat_id: String,
-    parse_mode: String
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parse_mode: Option<String>,
+    disable_notification: bool,
+    protect_content: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link_preview_options: Option<LinkPreviewDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_parameters: Option<ReplyParametersDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_markup: Option<ReplyMarkupDto>
+}
+
+/// The link preview options DTO, it is only sent to disable the preview.
+#[derive(Serialize)]
+pub struct LinkPreviewDto {
+    is_disabled: bool
+}
+
+impl LinkPreviewDto {
+    /// Make the options of the `nopreview` message, it is `None` if the preview is enabled.
+    pub fn new(is_nopreview: bool) -> Option<Self> {
+        if !is_nopreview {
+            return None;
+        }
+        return Some(Self { is_disabled: true });
+    }
+}
+
+/// The reply parameters DTO, the message is sent even if the replied message was deleted.
+#[derive(Serialize)]
+struct ReplyParametersDto {
+    message_id: i64,
+    allow_sending_without_reply: bool
+}
+
+/// The inline keyboard button DTO, it is a link button.
+#[derive(Serialize)]
+struct ButtonDto {
+    text: String,
+    url: String
+}
+
+/// The message reply markup DTO (`InlineKeyboardMarkup`).
+#[derive(Serialize)]
+pub struct ReplyMarkupDto {
+    inline_keyboard: Vec<Vec<ButtonDto>>
+}
+
+impl ReplyMarkupDto {
+    /// Make the inline keyboard of the message buttons, it is `None` if there are no buttons.
+    pub fn new(buttons: &[Vec<parser::Button>]) -> Option<Self> {
+        if buttons.is_empty() {
+            return None;
+        }
+        let inline_keyboard = buttons
+            .iter()
+            .map( |row| row
+                .iter()
+                .map( |button| ButtonDto { text: button.text.clone(), url: button.url.clone() } )
+                .collect()
+            )
+            .collect();
+        return Some(Self { inline_keyboard });
+    }
+}
+
+/// The poll option DTO (`InputPollOption`).
+#[derive(Serialize)]
+struct PollOptionDto {
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    text_parse_mode: Option<String>
+}
+
+impl PollOptionDto {
+    /// Make the options of the choices with the parse mode.
+    fn options(choices: Vec<String>, parse_mode: ParseMode) -> Vec<Self> {
+        return choices
+            .into_iter()
+            .map( |text| Self { text, text_parse_mode: parse_mode.api_name() } )
+            .collect();
+    }
+}
+
+/// The album item DTO (`InputMediaPhoto` and `InputMediaDocument`).
+#[derive(Serialize)]
+struct InputMediaDto {
+    #[serde(rename = "type")]
+    _type: String,
+    media: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    caption: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parse_mode: Option<String>
 }
 
 /// The send poll DTO
@@ -23,136 +123,453 @@ struct MessageDto {
 struct PollDto {
     chat_id: String,
     question: String,
-    options: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    question_parse_mode: Option<String>,
+    options: Vec<PollOptionDto>,
     is_anonymous: bool,
     #[serde(rename = "type")]
     _type: String,
     allows_multiple_answers: bool,
     correct_option_id: usize,
-    explanation: String
+    explanation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explanation_parse_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    open_period: Option<u64>,
+    disable_notification: bool
+}
+
+/// The section send options.
+#[derive(Default)]
+pub struct SendOptions {
+    /// The number of seconds the poll/question will be open for (5 to 600).
+    pub open_period: Option<u64>,
+    /// Send the section without notification, it is set by `silent:`.
+    pub silent: bool,
+    /// Pin the section after sending it, it is set by `pin:`.
+    pub pin: bool,
+    /// Override the `anonymous` config of the polls/questions.
+    pub is_anonymous: Option<bool>,
+    /// The last message of the previously sent section, the `reply_to: previous` message replies to it.
+    pub previous: Option<i64>
+}
+
+impl SendOptions {
+    /// Apply the directive to the options of the next section, 
+    /// the `wait:` directive will sleep here.
+    pub async fn apply(&mut self, directive: &parser::Directive) {
+        let logger = Log::new("sender");
+        match directive {
+            parser::Directive::Wait(duration) => {
+                logger.info(&format!("waiting {} seconds...", duration.as_secs()));
+                tokio::time::sleep(*duration).await;
+            },
+            parser::Directive::Pin => self.pin = true,
+            parser::Directive::Silent => self.silent = true
+        }
+    }
+
+    /// Pin the sent section if `pin:` was used.
+    pub async fn pin_if_needed(&self, telegram: &Telegram, sent: &SentMessage) {
+        if !self.pin { return; }
+        let logger = Log::new("sender");
+        // The first message of the section is pinned, it is the first part of the split message or the overflow message.
+        let message_id = sent.parts.first().copied().unwrap_or(sent.message_id);
+        let pin_result = telegram.request::<_, bool>("pinChatMessage", &json!({
+            "chat_id": sent.chat.id,
+            "message_id": message_id,
+            "disable_notification": self.silent
+        })).await;
+        if let Err(err) = pin_result {
+            logger.warn(&format!("Can NOT pin the message {}! {}", message_id, err));
+        }
+    }
+}
+
+/// Read the media file from the disk as a multipart part.
+fn file_part(media: &parser::Media) -> Result<Part, String> {
+    let bytes = std::fs::read(&media.file)
+        .map_err(|_| format!("Can NOT read the file `{}`!", media.path))?;
+    let file_name = media.file
+        .file_name()
+        .map( |name| name.to_string_lossy().to_string() )
+        .unwrap_or(media.path.clone());
+    return Ok(Part::bytes(bytes).file_name(file_name));
+}
+
+/// Make the multipart form of the album, every file is attached as `file<index>`.
+fn album_form(chat_id: &str, album: &parser::Album, options: &SendOptions) -> Result<Form, String> {
+    let mut form = Form::new()
+        .text("chat_id", chat_id.to_string())
+        .text("disable_notification", options.silent.to_string());
+    let mut input_media = Vec::new();
+    for (index, item) in album.items.iter().enumerate() {
+        let name = format!("file{}", index);
+        form = form.part(name.clone(), file_part(item)?);
+        input_media.push(InputMediaDto {
+            _type: item.kind.field().to_string(),
+            media: format!("attach://{}", name),
+            caption: item.caption.clone(),
+            parse_mode: item.caption.as_ref().and_then( |_| item.parse_mode.api_name() )
+        });
+    }
+    let input_media = serde_json::to_string(&input_media)
+        .map_err(|_| "Can NOT serialize the album!".to_string())?;
+    return Ok(form.text("media", input_media));
+}
+
+/// Make the multipart form of the media, the file is read from the disk.
+fn media_form(chat_id: &str, media: &parser::Media, options: &SendOptions) -> Result<Form, String> {
+    let mut form = Form::new()
+        .text("chat_id", chat_id.to_string())
+        .text("disable_notification", options.silent.to_string())
+        .part(media.kind.field().to_string(), file_part(media)?);
+    if let Some(caption) = &media.caption {
+        form = form.text("caption", caption.clone());
+        if let Some(parse_mode) = media.parse_mode.api_name() {
+            form = form.text("parse_mode", parse_mode);
+        }
+    }
+    return Ok(form);
 }
 
-/// Send .mcq abstraction tree to telegram.
-async fn send(abstraction_tree: parser::McqpAST, send_config: config::Config) {
-    let poll_api_url = format!("https://api.telegram.org/bot{}/sendpoll", send_config.bot_token);
-    let message_api_url = format!("https://api.telegram.org/bot{}/sendMessage", send_config.bot_token);
+/// Delete the sent messages of the section when its next message failed,
+/// the section is NOT recorded so they can NOT be unsent later.
+async fn delete_sent(telegram: &Telegram, chat_id: i64, message_ids: &[i64]) {
     let logger = Log::new("sender");
-    let client = Client::new();
-    for section in abstraction_tree.mcqps {
-        match section._type {
-            parser::McqpType::Message => {
-                let message = section.message.unwrap();
-                let res_ruslt = client.post(message_api_url.clone())
-                    .json(&MessageDto {
-                        chat_id: send_config.chat_id.clone(),
-                        text: message.m,
-                        parse_mode: "Markdown".to_string()
-                    })
-                    .send()
-                    .await;
-                if let Ok(res) = res_ruslt {
-                    if !res.status().is_success() {
-                        logger.error("Can NOT send the message!");
-                    }
-                    logger.info("message sended successfully");
-                } else {
-                    // Network error
-                    logger.error("Network error, can NOT make a post request!");
+    for message_id in message_ids {
+        if let Err(err) = telegram.delete_message(chat_id, *message_id).await {
+            logger.warn(&format!("Can NOT delete the sent message {}! {}", message_id, err));
+        }
+    }
+}
+
+/// Send the section to the chat.
+pub async fn send_section(
+    telegram: &Telegram, 
+    chat_id: &str, 
+    section: &parser::Mcqp, 
+    options: &SendOptions
+) -> Result<SentMessage, String> {
+    return match section._type {
+        parser::McqpType::Message => {
+            let message = section.message.as_ref().unwrap();
+            // The long message is sent as parts, the last part is the sent message.
+            let mut parts = Vec::new();
+            let mut sent: Option<SentMessage> = None;
+            let message_parts = message.parts();
+            let parts_len = message_parts.len();
+            for (index, part) in message_parts.into_iter().enumerate() {
+                if let Some(previous) = &sent {
+                    parts.push(previous.message_id);
                 }
-            }
-            parser::McqpType::Poll => {
-                let poll = section.poll.unwrap();
-                let res_ruslt = client.post(poll_api_url.clone())
-                    .json(&PollDto {
-                        chat_id: send_config.chat_id.clone(),
-                        question: poll.question(),
-                        options: poll.choices(),
-                        is_anonymous: true,
-                        _type: "regular".to_string(),
-                        allows_multiple_answers: false,
-                        correct_option_id: 1,
-                        explanation: "".to_string()
-                    })
-                    .send()
-                    .await;
-                if let Ok(res) = res_ruslt {
-                    if !res.status().is_success() {
-                        logger.error("Can NOT send the poll!");
+                // The first part is the reply.
+                let reply_parameters = options.previous
+                    .filter( |_| message.is_reply && index == 0 )
+                    .map( |message_id| ReplyParametersDto { message_id, allow_sending_without_reply: true } );
+                let result = telegram.request("sendMessage", &MessageDto {
+                    chat_id: chat_id.to_string(),
+                    text: part,
+                    parse_mode: message.parse_mode.api_name(),
+                    disable_notification: options.silent || message.is_silent,
+                    protect_content: message.is_protected,
+                    link_preview_options: LinkPreviewDto::new(message.is_nopreview),
+                    reply_parameters,
+                    // The buttons are under the last part.
+                    reply_markup: if index + 1 == parts_len { ReplyMarkupDto::new(&message.buttons) } else { None }
+                }).await;
+                sent = match result {
+                    Ok(part) => Some(part),
+                    Err(err) => {
+                        if let Some(previous) = &sent {
+                            delete_sent(telegram, previous.chat.id, &parts).await;
+                        }
+                        return Err(err);
                     }
-                    logger.info("poll sended successfully");
-                } else {
-                    // Network error
-                    logger.error("Network error, can NOT make a post request!");
-                }
+                };
             }
-            parser::McqpType::MCPoll => {
-                let poll = section.poll.unwrap();
-                let res_ruslt = client.post(poll_api_url.clone())
-                    .json(&PollDto {
-                        chat_id: send_config.chat_id.clone(),
-                        question: poll.question(),
-                        options: poll.choices(),
-                        is_anonymous: true,
-                        _type: "regular".to_string(),
-                        allows_multiple_answers: true,
-                        correct_option_id: 1,
-                        explanation: "".to_string()
-                    })
-                    .send()
-                    .await;
-                if let Ok(res) = res_ruslt {
-                    if !res.status().is_success() {
-                        logger.error("Can NOT send the poll!");
-                    }
-                    logger.info("mc poll sended successfully");
-                } else {
-                    // Network error
-                    logger.error("Network error, can NOT make a post request!");
-                }
+            let mut sent = sent.unwrap();
+            sent.parts = parts;
+            Ok(sent)
+        }
+        parser::McqpType::Poll | parser::McqpType::MCPoll => {
+            let poll = section.poll.as_ref().unwrap();
+            telegram.request("sendPoll", &PollDto {
+                chat_id: chat_id.to_string(),
+                question: poll.question(),
+                question_parse_mode: poll.parse_mode().api_name(),
+                options: PollOptionDto::options(poll.choices(), poll.parse_mode()),
+                is_anonymous: options.is_anonymous.unwrap_or(poll.is_anonymous()),
+                _type: "regular".to_string(),
+                allows_multiple_answers: section._type == parser::McqpType::MCPoll,
+                correct_option_id: 1,
+                explanation: "".to_string(),
+                explanation_parse_mode: None,
+                open_period: options.open_period,
+                disable_notification: options.silent
+            }).await
+        }
+        parser::McqpType::Question => {
+            let question = section.question.as_ref().unwrap();
+            // The long question/options are sent in a message before the quiz.
+            let mut parts = Vec::new();
+            let mut overflow_chat = None;
+            if question.is_overflow() {
+                let overflow = telegram.request::<_, SentMessage>("sendMessage", &MessageDto {
+                    chat_id: chat_id.to_string(),
+                    text: question.overflow_message(),
+                    parse_mode: question.parse_mode().api_name(),
+                    disable_notification: options.silent,
+                    protect_content: false,
+                    link_preview_options: None,
+                    reply_parameters: None,
+                    reply_markup: None
+                }).await?;
+                parts.push(overflow.message_id);
+                overflow_chat = Some(overflow.chat.id);
             }
-            parser::McqpType::Question => {
-                let question = section.question.unwrap();
-                let res_ruslt = client.post(poll_api_url.clone())
-                    .json(&PollDto {
-                        chat_id: send_config.chat_id.clone(),
-                        question: question.question(),
-                        options: question.choices(),
-                        is_anonymous: true,
-                        _type: "quiz".to_string(),
-                        allows_multiple_answers: false,
-                        correct_option_id: question.answer() as usize,
-                        explanation: question.note().unwrap_or("".to_string())
-                    })
-                    .send()
-                    .await;
-                if let Ok(res) = res_ruslt {
-                    if !res.status().is_success() {
-                        logger.error("Can NOT send the question!");
+            let result = telegram.request::<_, SentMessage>("sendPoll", &PollDto {
+                chat_id: chat_id.to_string(),
+                question: question.quiz_question(),
+                question_parse_mode: question.parse_mode().api_name(),
+                options: PollOptionDto::options(question.quiz_choices(), question.parse_mode()),
+                is_anonymous: options.is_anonymous.unwrap_or(question.is_anonymous()),
+                _type: "quiz".to_string(),
+                allows_multiple_answers: false,
+                correct_option_id: question.answer() as usize,
+                explanation: question.note().unwrap_or("".to_string()),
+                explanation_parse_mode: question.note().and_then( |_| question.parse_mode().api_name() ),
+                open_period: options.open_period,
+                disable_notification: options.silent
+            }).await;
+            let mut sent = match result {
+                Ok(sent) => sent,
+                Err(err) => {
+                    if let Some(chat_id) = overflow_chat {
+                        delete_sent(telegram, chat_id, &parts).await;
                     }
-                    logger.info("question sended successfully");
-                } else {
-                    // Network error
-                    logger.error("Network error, can NOT make a post request!");
+                    return Err(err);
                 }
+            };
+            sent.parts = parts;
+            Ok(sent)
+        }
+        parser::McqpType::Media => {
+            let media = section.media.as_ref().unwrap();
+            telegram.upload(media.kind.method(), media_form(chat_id, media, options)?).await
+        }
+        parser::McqpType::Album => {
+            let album = section.album.as_ref().unwrap();
+            let mut sent = telegram
+                .upload::<Vec<SentMessage>>("sendMediaGroup", album_form(chat_id, album, options)?)
+                .await?;
+            // The album is recorded as its last message, the other messages are its parts.
+            let mut last = sent.pop().ok_or("The album has no messages!".to_string())?;
+            last.parts = sent.into_iter().map( |message| message.message_id ).collect();
+            Ok(last)
+        }
+        parser::McqpType::Directive => Err("The directive can NOT be sent!".to_string())
+    };
+}
+
+/// Make the ledger record of the sent section.
+pub fn record(section: &parser::Mcqp, sent: SentMessage) -> Record {
+    return Record {
+        section: section.number,
+        id: section.meta.id.clone(),
+        hash: section.hash(),
+        kind: section._type.name().to_string(),
+        message_id: sent.message_id,
+        chat_id: sent.chat.id,
+        poll_id: sent.poll.map( |poll| poll.id ),
+        sent_at: utils::now(),
+        deleted: false,
+        question: section.question.as_ref().map( |question| question.question() ),
+        answer: section.question.as_ref().map( |question| question.answer() ),
+        parts: sent.parts,
+        flags: section.message
+            .as_ref()
+            .map( |message| message.flags().iter().map( |flag| flag.to_string() ).collect() )
+            .unwrap_or_default()
+    };
+}
+
+/// Check if the content was sent to the chat before, it will ask the user to 
+/// confirm sending the same content again. With `new_only` the sent sections 
+/// will be removed from the tree.
+pub fn check_sent(abstraction_tree: &mut parser::McqpAST, ledger: &Ledger, chat_id: &str, new_only: bool) {
+    let logger = Log::new("sender");
+    if new_only {
+        abstraction_tree.retain( |section| !ledger.is_sent(&section.hash(), chat_id) );
+        // The `wait:` directives are always kept, so the sections are counted.
+        let new_count = abstraction_tree.poll_count 
+            + abstraction_tree.question_count 
+            + abstraction_tree.message_count 
+            + abstraction_tree.media_count;
+        if new_count == 0 {
+            logger.info("All sections were already sent to the chat, nothing to send.");
+            std::process::exit(0);
+        }
+        logger.info(
+            &format!(
+                "new {}/poll and {}/question and {}/message", 
+                abstraction_tree.poll_count,
+                abstraction_tree.question_count,
+                abstraction_tree.message_count
+            )
+        );
+        return;
+    }
+    if let Some(run) = ledger.find_run(&abstraction_tree.hash(), chat_id) {
+        logger.warn(
+            &format!(
+                "The same content was already sent to the chat at {} (run #{}).", 
+                utils::format_time(run.sent_at), 
+                run.id
+            )
+        );
+    } else {
+        let sent_count = abstraction_tree.mcqps
+            .iter()
+            .filter( |section| ledger.is_sent(&section.hash(), chat_id) )
+            .count();
+        if sent_count == 0 { return; }
+        logger.warn(
+            &format!(
+                "{} of {} sections were already sent to the chat, use `--new-only` to send the new sections only.", 
+                sent_count, 
+                abstraction_tree.mcqps.len()
+            )
+        );
+    }
+    let answer = utils::input("Send it again? [y/N]: ");
+    if answer.to_lowercase() != "y" {
+        logger.info("Nothing was sent.");
+        std::process::exit(0);
+    }
+}
+
+/// Close the sent polls/questions of the run, and then send the results message.
+async fn close_polls(telegram: &Telegram, ledger: &Ledger, run_id: u64, chat_id: &str) {
+    let logger = Log::new("sender");
+    let mut closed_polls: Vec<(&Record, ClosedPoll)> = Vec::new();
+    let records = ledger.runs
+        .iter()
+        .filter( |run| run.id == run_id )
+        .flat_map( |run| run.records.iter() )
+        .filter( |record| record.poll_id.is_some() && !record.deleted );
+    for record in records {
+        let close_result = telegram.request::<_, ClosedPoll>(
+            "stopPoll", 
+            &json!({"chat_id": record.chat_id, "message_id": record.message_id})
+        ).await;
+        match close_result {
+            Ok(poll) => {
+                closed_polls.push((record, poll));
+                logger.info(&format!("{} closed successfully", record.kind));
+            },
+            Err(err) => logger.warn(&format!("Can NOT close the {} {}! {}", record.kind, record.message_id, err))
+        }
+    }
+    if closed_polls.is_empty() {
+        logger.info("There is no closed polls, the results message will not be sent.");
+        return;
+    }
+    for part in summary::format(&closed_polls) {
+        let results_result = telegram.request::<_, SentMessage>("sendMessage", &MessageDto {
+            chat_id: chat_id.to_string(),
+            text: part,
+            parse_mode: Some("Markdown".to_string()),
+            disable_notification: false,
+            protect_content: false,
+            link_preview_options: None,
+            reply_parameters: None,
+            reply_markup: None
+        }).await;
+        if let Err(err) = results_result {
+            logger.error(&format!("Can NOT send the results message! {}", err));
+        }
+    }
+    logger.info("results message sended successfully");
+}
+
+/// Send .mcq abstraction tree to telegram, every sent section will be recorded 
+/// in the ledger. It returns the run id, or the error of the section that can 
+/// NOT be sent (the sections before it are recorded).
+pub async fn send(
+    abstraction_tree: parser::McqpAST, 
+    send_config: &config::Config, 
+    file: &str, 
+    ledger: &mut Ledger
+) -> Result<u64, String> {
+    let logger = Log::new("sender");
+    let telegram = Telegram::new(&send_config.bot_token);
+    let run_id = ledger.new_run(
+        &file::absolute(file), 
+        &send_config.chat_id, 
+        Some(abstraction_tree.hash())
+    );
+    let mut options = SendOptions::default();
+    for section in abstraction_tree.mcqps {
+        if let Some(directive) = &section.directive {
+            options.apply(directive).await;
+            continue;
+        }
+        match send_section(&telegram, &send_config.chat_id, &section, &options).await {
+            Ok(sent) => {
+                options.pin_if_needed(&telegram, &sent).await;
+                options = SendOptions { previous: Some(sent.message_id), ..Default::default() };
+                ledger.record(run_id, record(&section, sent));
+                logger.info(&format!("{} sended successfully", section._type.name()));
             }
+            Err(err) => return Err(format!(
+                "Can NOT send the {}! {} The sent sections are recorded as run #{}.", 
+                section._type.name(), 
+                err,
+                run_id
+            ))
         }
     }
+    logger.info(&format!("The sent sections are recorded as run #{}.", run_id));
+    return Ok(run_id);
 }
 
 pub async fn main(command: &ArgMatches) {
     let logger = Log::new("sender");
     let file = command.get_one::<String>("FILE").unwrap();
-    let file_state = file::state(file.clone());
+    let close_after = command.get_one::<String>("close-after").map( |duration| {
+        utils::parse_duration(duration)
+            .unwrap_or_else(|| logger.error("Invalid `--close-after` duration, expected a duration like `10m`!"))
+    });
+    let send_at = command.get_one::<String>("at").map( |time| {
+        let time = utils::parse_time(time)
+            .unwrap_or_else(|| logger.error("Invalid `--at` time, expected a date and a time with an offset like `2026-11-02T08:00+03:00` or `2026-11-02T05:00Z`!"));
+        if time <= utils::now() {
+            logger.error("The `--at` time must be in the future!");
+        }
+        time
+    });
     let mut send_config = config::Config::new();
-    if file_state == file::FileState::NotFound {
-        logger.error("File NOT found!");
-    } else if file_state == file::FileState::NotMcqpFile {
-        logger.error("File type is NOT .mcq!");
-    }
-    let mut abstraction_tree = parser::McqpAST::new(
-        std::path::PathBuf::new().join(file)
-    );
-    abstraction_tree.parse();
+    let mut abstraction_tree = parser::parse_file(file, &logger);
+    Filter::new(command).apply(&mut abstraction_tree);
     send_config.get_config();
-    send(abstraction_tree, send_config).await;
-}
\ No newline at end of file
+    let mut ledger = Ledger::load();
+    if !command.get_flag("force") {
+        check_sent(&mut abstraction_tree, &ledger, &send_config.chat_id, command.get_flag("new-only"));
+    }
+    if let Some(send_at) = send_at {
+        logger.info(&format!("The file will be sent at {}, keep this running...", utils::format_time(send_at)));
+        // The time may be passed while waiting for the confirmation, then it is sent now.
+        tokio::time::sleep(std::time::Duration::from_secs(send_at.saturating_sub(utils::now()))).await;
+        // The ledger may be changed by other commands while waiting.
+        ledger = Ledger::load();
+    }
+    let run_id = send(abstraction_tree, &send_config, file, &mut ledger)
+        .await
+        .unwrap_or_else(|err| logger.error(&err));
+    if let Some(close_after) = close_after {
+        logger.info(&format!("The polls will be closed after {} seconds...", close_after.as_secs()));
+        tokio::time::sleep(close_after).await;
+        close_polls(&Telegram::new(&send_config.bot_token), &ledger, run_id, &send_config.chat_id).await;
+    }
+}
@@ -0,0 +1,204 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use std::collections::HashMap;
+use clap::ArgMatches;
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::config;
+use crate::filter::Filter;
+use crate::log::Log;
+use crate::parser::{self, McqpType};
+use crate::results::PollAnswer;
+use crate::send::{self, SendOptions};
+use crate::telegram::{self, SentMessage, Telegram};
+
+/// The long polling timeout in seconds.
+const POLLING_TIMEOUT: u64 = 30;
+
+/// The bot update, only the private messages and the poll answers are used.
+#[derive(Deserialize)]
+struct BotUpdate {
+    update_id: i64,
+    message: Option<BotMessage>,
+    poll_answer: Option<PollAnswer>
+}
+
+/// The message sent to the bot.
+#[derive(Deserialize)]
+struct BotMessage {
+    chat: BotChat,
+    text: Option<String>
+}
+
+/// The chat of the message.
+#[derive(Deserialize)]
+struct BotChat {
+    id: i64,
+    #[serde(rename = "type")]
+    _type: String
+}
+
+/// The user quiz session.
+struct Session {
+    /// The index of the next section.
+    next: usize,
+    /// The poll id of the section that waits for the user answer.
+    waiting_poll: Option<String>,
+    /// The number of correct answers.
+    score: usize,
+    /// The missed questions.
+    missed: Vec<String>
+}
+
+/// The quiz bot, it serves the .mcq file to the users in private chats.
+struct QuizBot {
+    telegram: Telegram,
+    abstraction_tree: parser::McqpAST,
+    /// The users sessions by the chat id.
+    sessions: HashMap<i64, Session>
+}
+
+impl QuizBot {
+    /// Send a plain text message to the chat.
+    async fn reply(&self, chat_id: i64, text: &str) {
+        let logger = Log::new("serve");
+        let reply_result = self.telegram.request::<_, SentMessage>(
+            "sendMessage", 
+            &json!({"chat_id": chat_id, "text": text})
+        ).await;
+        if let Err(err) = reply_result {
+            logger.warn(&format!("Can NOT send the message to {}! {}", chat_id, err));
+        }
+    }
+
+    /// Handle the private message commands (`/start` and `/quiz`).
+    async fn handle_message(&mut self, message: BotMessage) {
+        if message.chat._type != "private" { return; }
+        let chat_id = message.chat.id;
+        match message.text.unwrap_or_default().split_whitespace().next() {
+            Some("/quiz") => {
+                self.sessions.insert(chat_id, Session {
+                    next: 0,
+                    waiting_poll: None,
+                    score: 0,
+                    missed: Vec::new()
+                });
+                self.next(chat_id).await;
+            },
+            _ => {
+                let text = format!(
+                    "Hi there 👋,\nThis quiz has {} questions and {} polls.\nSend /quiz to start it, \
+                    the next question will be sent after you answer the current one.",
+                    self.abstraction_tree.question_count,
+                    self.abstraction_tree.poll_count
+                );
+                self.reply(chat_id, &text).await;
+            }
+        }
+    }
+
+    /// Handle the user answer, check it and send the next section.
+    async fn handle_poll_answer(&mut self, poll_answer: PollAnswer) {
+        let Some(user) = poll_answer.user else { return; };
+        let Some(session) = self.sessions.get_mut(&user.id) else { return; };
+        if session.waiting_poll.as_deref() != Some(&poll_answer.poll_id) { return; }
+        // The waiting section is the previous one.
+        if let Some(question) = self.abstraction_tree.mcqps[session.next - 1].question.as_ref() {
+            if poll_answer.option_ids.first().map( |&option| option as i8 ) == Some(question.answer()) {
+                session.score += 1;
+            } else {
+                session.missed.push(question.plain_question());
+            }
+        }
+        session.waiting_poll = None;
+        self.next(user.id).await;
+    }
+
+    /// Send the sections to the user until a poll/question is sent, 
+    /// or send the score if there is no more sections.
+    async fn next(&mut self, chat_id: i64) {
+        let logger = Log::new("serve");
+        let options = SendOptions {
+            is_anonymous: Some(false),
+            ..Default::default()
+        };
+        loop {
+            let Some(session) = self.sessions.get(&chat_id) else { return; };
+            let Some(section) = self.abstraction_tree.mcqps.get(session.next) else { break; };
+            let next = session.next + 1;
+            // The directives are for the channel sends, so they are skipped here.
+            if section._type == McqpType::Directive {
+                self.sessions.get_mut(&chat_id).unwrap().next = next;
+                continue;
+            }
+            let sent_result = send::send_section(&self.telegram, &chat_id.to_string(), section, &options).await;
+            let session = self.sessions.get_mut(&chat_id).unwrap();
+            session.next = next;
+            match sent_result {
+                Ok(sent) => {
+                    if let Some(poll) = sent.poll {
+                        session.waiting_poll = Some(poll.id);
+                        return;
+                    }
+                },
+                Err(err) => {
+                    logger.warn(&format!("Can NOT send the {} to {}! {}", section._type.name(), chat_id, err));
+                    self.sessions.remove(&chat_id);
+                    return;
+                }
+            }
+        }
+        let Some(session) = self.sessions.remove(&chat_id) else { return; };
+        let total = self.abstraction_tree.question_count as usize;
+        let mut text = format!("The quiz is finished 🎉\nYour score: {}/{}", session.score, total);
+        if !session.missed.is_empty() {
+            text += "\n\nThe missed questions:";
+            session.missed.iter().for_each( |question| text += &format!("\n- {}", question) );
+        }
+        text += "\n\nSend /quiz to try again.";
+        self.reply(chat_id, &text).await;
+        logger.info(&format!("the user {} finished the quiz with {}/{}", chat_id, session.score, total));
+    }
+}
+
+/// Run the quiz bot until it is stopped.
+pub async fn main(command: &ArgMatches) {
+    let logger = Log::new("serve");
+    let file = command.get_one::<String>("FILE").unwrap();
+    let mut abstraction_tree = parser::parse_file(file, &logger);
+    Filter::new(command).apply(&mut abstraction_tree);
+    let mut send_config = config::Config::new();
+    send_config.get_config();
+    let mut bot = QuizBot {
+        telegram: Telegram::new(&send_config.bot_token),
+        abstraction_tree,
+        sessions: HashMap::new()
+    };
+    logger.info("The quiz bot is running, press Ctrl+C to stop it.");
+    let mut offset: i64 = 0;
+    loop {
+        let updates_result = bot.telegram.request::<_, Vec<BotUpdate>>("getUpdates", &json!({
+            "offset": offset,
+            "timeout": POLLING_TIMEOUT,
+            "allowed_updates": telegram::ALLOWED_UPDATES
+        })).await;
+        let updates = match updates_result {
+            Ok(updates) => updates,
+            Err(err) => {
+                logger.warn(&format!("Can NOT get the updates, retrying... {}", err));
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                continue;
+            }
+        };
+        for update in updates {
+            offset = update.update_id + 1;
+            if let Some(message) = update.message {
+                bot.handle_message(message).await;
+            } else if let Some(poll_answer) = update.poll_answer {
+                bot.handle_poll_answer(poll_answer).await;
+            }
+        }
+    }
+}
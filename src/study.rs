@@ -0,0 +1,260 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use std::collections::HashMap;
+use std::io::Read;
+use clap::ArgMatches;
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+
+use crate::filter::Filter;
+use crate::log::Log;
+use crate::parser::{self, Question};
+use crate::practice;
+use crate::utils;
+
+/// The seconds of one day.
+const DAY: u64 = 86400;
+/// The default number of the new questions in one study session.
+const DEFAULT_NEW_LIMIT: usize = 20;
+/// The interval in days that makes the question mature.
+const MATURE_INTERVAL: u64 = 21;
+
+/// The study progress of one question, it is scheduled by the SM-2 algorithm.
+#[derive(Serialize, Deserialize, Clone)]
+struct Card {
+    /// The number of the right reviews in a row.
+    repetitions: u32,
+    /// The days until the next review.
+    interval: u64,
+    /// The easiness factor, it is 2.5 for the new cards and never goes below 1.3.
+    ease: f64,
+    /// The time of the next review (unix time).
+    due: u64,
+    /// The number of all reviews.
+    reviews: u32,
+    /// The number of the wrong reviews.
+    lapses: u32
+}
+
+impl Card {
+    fn new() -> Self {
+        return Self {
+            repetitions: 0,
+            interval: 0,
+            ease: 2.5,
+            due: 0,
+            reviews: 0,
+            lapses: 0
+        };
+    }
+
+    /// Schedule the next review by the answer quality (0-5),
+    /// the quality below 3 means that the answer was wrong.
+    fn review(&mut self, quality: u8) {
+        let quality = quality.min(5) as f64;
+        self.reviews += 1;
+        if quality < 3.0 {
+            self.lapses += 1;
+            self.repetitions = 0;
+            self.interval = 1;
+        } else {
+            self.interval = match self.repetitions {
+                0 => 1,
+                1 => 6,
+                _ => (self.interval as f64 * self.ease).round() as u64
+            };
+            self.repetitions += 1;
+        }
+        self.ease = (self.ease + 0.1 - (5.0 - quality) * (0.08 + (5.0 - quality) * 0.02)).max(1.3);
+        self.due = utils::now() + self.interval * DAY;
+    }
+}
+
+/// The study progress of all questions, keyed by the question content hash 
+/// (`Question::content_hash`), so the same question has the same progress in 
+/// all files even if the counter is on.
+#[derive(Serialize, Deserialize, Default)]
+struct Progress {
+    cards: HashMap<String, Card>
+}
+
+impl Progress {
+    /// Read the study progress, it returns the default if there is no saved progress.
+    fn load(path: &std::path::PathBuf) -> Self {
+        let logger = Log::new("study");
+        if !path.exists() {
+            return Self::default();
+        }
+        let mut file = std::fs::File::open(path)
+            .unwrap_or_else(|_| logger.error("Can NOT open the study progress file!"));
+        let mut progress_buf = String::new();
+        file
+            .read_to_string(&mut progress_buf)
+            .unwrap_or_else(|_| logger.error("Can NOT read the study progress file!"));
+        return serde_json::from_str(&progress_buf)
+            .unwrap_or_else(|_| logger.error(&format!("Can NOT parse the study progress ({})!", path.display())));
+    }
+
+    /// Save the study progress.
+    fn save(&self, path: &std::path::Path) {
+        let logger = Log::new("study");
+        let json = serde_json::to_string(&self)
+            .unwrap_or_else(|_| logger.error("Can NOT make json object!"));
+        utils::write_file(path, &json)
+            .unwrap_or_else(|_| logger.error("Can NOT write to the study progress file!"));
+    }
+}
+
+/// Ask how easy was the right answer, it returns the SM-2 quality.
+fn ask_quality() -> u8 {
+    loop {
+        let input = utils::input("How easy was it? (h)ard, (g)ood or (e)asy [g]: ");
+        match input.to_lowercase().as_str() {
+            "h" | "hard" => return 3,
+            "" | "g" | "good" => return 4,
+            "e" | "easy" => return 5,
+            _ => println!("{}", "Please enter h, g or e.".yellow())
+        }
+    }
+}
+
+/// Show the study progress of the file questions.
+fn stats(questions: &Vec<(String, &Question)>, progress: &Progress) {
+    let now = utils::now();
+    let cards = questions
+        .iter()
+        .filter_map( |(hash, _)| progress.cards.get(hash) )
+        .collect::<Vec<&Card>>();
+    let due = cards.iter().filter( |card| card.due <= now ).count();
+    let mature = cards.iter().filter( |card| card.interval >= MATURE_INTERVAL ).count();
+    let reviews = cards.iter().map( |card| card.reviews ).sum::<u32>();
+    let lapses = cards.iter().map( |card| card.lapses ).sum::<u32>();
+    let retention = if reviews == 0 {
+        String::from("-")
+    } else {
+        format!("{:.1}%", (reviews - lapses) as f64 * 100.0 / reviews as f64)
+    };
+    println!(
+        "Questions: {}\nNew: {}\nDue: {}\nLearning: {}\nMature: {}\nReviews: {}\nLapses: {}\nRetention: {}",
+        questions.len().to_string().green(),
+        (questions.len() - cards.len()).to_string().green(),
+        due.to_string().green(),
+        (cards.len() - mature).to_string().green(),
+        mature.to_string().green(),
+        reviews.to_string().green(),
+        lapses.to_string().green(),
+        retention.green()
+    );
+}
+
+/// Study the file questions with spaced repetition.
+pub fn main(command: &ArgMatches) {
+    let logger = Log::new("study");
+    let file = command.get_one::<String>("FILE").unwrap();
+    let mut abstraction_tree = parser::parse_file(file, &logger);
+    Filter::new(command).apply(&mut abstraction_tree);
+    let progress_path = utils::data_path("study.json");
+    let mut progress = Progress::load(&progress_path);
+    let mut questions = abstraction_tree.mcqps
+        .iter()
+        .filter_map( |section| section.question.as_ref().map( |question| (question.content_hash(), question) ) )
+        .collect::<Vec<(String, &Question)>>();
+    // The same question can be repeated in the file.
+    let mut seen = std::collections::HashSet::new();
+    questions.retain( |(hash, _)| seen.insert(hash.clone()) );
+    if questions.is_empty() {
+        logger.error("There are no questions to study in the file!");
+    }
+    if command.get_flag("stats") {
+        stats(&questions, &progress);
+        return;
+    }
+    let new_limit = command.get_one::<String>("new")
+        .map( |limit| limit.parse::<usize>()
+            .unwrap_or_else(|_| logger.error("The new questions limit must be a number!")) )
+        .unwrap_or(DEFAULT_NEW_LIMIT);
+    let now = utils::now();
+    // The due questions first, the most overdue first, then the new questions in the file order.
+    let mut due = questions
+        .iter()
+        .filter( |(hash, _)| progress.cards.get(hash).is_some_and( |card| card.due <= now ) )
+        .collect::<Vec<&(String, &Question)>>();
+    due.sort_by_key( |(hash, _)| progress.cards[hash].due );
+    let new = questions
+        .iter()
+        .filter( |(hash, _)| !progress.cards.contains_key(hash) )
+        .take(new_limit);
+    let session = due.into_iter().chain(new).collect::<Vec<&(String, &Question)>>();
+    if session.is_empty() {
+        let next_due = questions
+            .iter()
+            .filter_map( |(hash, _)| progress.cards.get(hash) )
+            .map( |card| card.due )
+            .min()
+            .unwrap_or(now);
+        logger.info(&format!("Nothing to study now, the next review is at {}.", utils::format_time(next_due)));
+        return;
+    }
+    let total = session.len();
+    let mut score = 0;
+    let mut answered = 0;
+    for (index, (hash, question)) in session.into_iter().enumerate() {
+        let order = (0..question.choices().len()).collect::<Vec<usize>>();
+        let Some(is_right) = practice::ask(question, &order, index + 1, total) else { break; };
+        let quality = if is_right { ask_quality() } else { 1 };
+        let card = progress.cards.entry(hash.clone()).or_insert_with(Card::new);
+        card.review(quality);
+        println!("{} {}", "The next review is at".cyan(), utils::format_time(card.due));
+        progress.save(&progress_path);
+        answered += 1;
+        if is_right {
+            score += 1;
+        }
+    }
+    println!("\n{} {}/{}", "Your score:".bold(), score.to_string().green(), answered);
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn review_right_answers() {
+        let mut card = Card::new();
+        card.review(5);
+        assert_eq!((card.repetitions, card.interval), (1, 1));
+        card.review(5);
+        assert_eq!((card.repetitions, card.interval), (2, 6));
+        card.review(5);
+        // The interval is multiplied by the ease before the review (2.7).
+        assert_eq!((card.repetitions, card.interval), (3, 16));
+        assert!((card.ease - 2.8).abs() < 1e-9);
+        assert_eq!((card.reviews, card.lapses), (3, 0));
+        assert!(card.due >= utils::now() + 16 * DAY - 1);
+    }
+
+    #[test]
+    fn review_wrong_answer() {
+        let mut card = Card::new();
+        card.review(5);
+        card.review(5);
+        card.review(1);
+        assert_eq!((card.repetitions, card.interval), (0, 1));
+        assert_eq!((card.reviews, card.lapses), (3, 1));
+        assert!((card.ease - 2.16).abs() < 1e-9);
+    }
+
+    #[test]
+    fn review_ease_floor() {
+        let mut card = Card::new();
+        for _ in 0..10 {
+            card.review(0);
+        }
+        assert!((card.ease - 1.3).abs() < 1e-9);
+        // The quality is at most 5.
+        card.review(9);
+        assert!((card.ease - 1.4).abs() < 1e-9);
+    }
+}
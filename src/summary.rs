@@ -0,0 +1,73 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use serde::Deserialize;
+
+use crate::ledger::Record;
+use crate::limits;
+use crate::markup::ParseMode;
+
+/// The closed poll (`stopPoll` result).
+#[derive(Deserialize)]
+pub struct ClosedPoll {
+    /// The poll question.
+    pub question: String,
+    /// The poll options with the votes.
+    pub options: Vec<PollOption>,
+    /// The number of users that voted.
+    pub total_voter_count: u64
+}
+
+/// The poll option with the votes.
+#[derive(Deserialize)]
+pub struct PollOption {
+    pub text: String,
+    pub voter_count: u64
+}
+
+/// Return the percentage of the votes.
+fn percentage(votes: u64, total: u64) -> u64 {
+    if total == 0 { return 0; }
+    return votes * 100 / total;
+}
+
+/// Make the markdown results messages of the closed polls, the questions 
+/// will show the correct answers percentage, and the polls will show the 
+/// most voted option. The long results are split between the polls into 
+/// several messages.
+/// 
+/// ### Example:
+/// ```
+/// let messages = summary::format(&closed_polls);
+/// // *Results*
+/// //
+/// // 1.How are you today?
+/// // ✅ 75% correct (3/4)
+/// ```
+pub fn format(closed_polls: &Vec<(&Record, ClosedPoll)>) -> Vec<String> {
+    let mut message = String::from("*Results*\n");
+    for (record, poll) in closed_polls {
+        message += &format!("\n{}\n", ParseMode::Markdown.escape(&poll.question));
+        if let Some(answer) = record.answer {
+            let correct = poll.options
+                .get(answer as usize)
+                .map( |option| option.voter_count )
+                .unwrap_or(0);
+            message += &format!(
+                "✅ {}% correct ({}/{})\n", 
+                percentage(correct, poll.total_voter_count), 
+                correct, 
+                poll.total_voter_count
+            );
+        } else if let Some(option) = poll.options.iter().max_by_key( |option| option.voter_count ) {
+            message += &format!(
+                "📊 {} - {}% ({}/{})\n", 
+                ParseMode::Markdown.escape(&option.text),
+                percentage(option.voter_count, poll.total_voter_count), 
+                option.voter_count, 
+                poll.total_voter_count
+            );
+        }
+    }
+    return limits::split(&message, ParseMode::Markdown, &limits::MESSAGE).unwrap_or(vec![message]);
+}
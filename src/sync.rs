@@ -0,0 +1,211 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use clap::ArgMatches;
+use serde::Serialize;
+
+use crate::config;
+use crate::file;
+use crate::filter::Filter;
+use crate::ledger::Ledger;
+use crate::log::Log;
+use crate::parser::{self, Mcqp, McqpType};
+use crate::send;
+use crate::telegram::{SentMessage, Telegram};
+
+/// The edit message DTO
+#[derive(Serialize)]
+struct EditMessageDto {
+    chat_id: i64,
+    message_id: i64,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parse_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    link_preview_options: Option<send::LinkPreviewDto>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reply_markup: Option<send::ReplyMarkupDto>
+}
+
+/// The last sent state of a section.
+struct SentState {
+    key: String,
+    hash: String,
+    kind: String,
+    chat_id: i64,
+    message_id: i64,
+    /// The message ids of the previous messages of the section.
+    parts: Vec<i64>,
+    /// The message flags when it was sent.
+    flags: Vec<String>
+}
+
+/// The sync action of a section.
+enum SyncAction<'a> {
+    /// The section did not change.
+    Keep(&'a SentState),
+    /// The message changed, it will be edited.
+    Edit(&'a Mcqp, &'a SentState),
+    /// The poll/question or the split message changed, it will be deleted and sent again.
+    Resend(&'a Mcqp, &'a SentState),
+    /// The section was not sent before.
+    Send(&'a Mcqp),
+    /// The section is not in the file anymore.
+    Delete(&'a SentState)
+}
+
+/// Check if the sent message can be edited to the section, the 
+/// split messages have more than one message and the message
+/// flags can only be set when sending.
+fn is_editable(section: &Mcqp, state: &SentState) -> bool {
+    return section._type == McqpType::Message
+        && state.kind == section._type.name()
+        && state.parts.is_empty()
+        && section.message.as_ref().is_some_and( |message| {
+            // The flags can NOT be changed by editing the message.
+            message.parts().len() == 1 && message.flags() == state.flags
+        });
+}
+
+/// Make the sync plan, compare the sections with the last sent state.
+fn plan<'a>(sections: &'a [Mcqp], sent: &'a [SentState], keys: &[String], prune: bool) -> Vec<SyncAction<'a>> {
+    let mut actions = sections
+        .iter()
+        .filter( |section| section._type != McqpType::Directive )
+        .map( |section| {
+            match sent.iter().find( |state| state.key == section.key() ) {
+                Some(state) if state.hash == section.hash() => SyncAction::Keep(state),
+                // The split messages can NOT be edited, they are sent again.
+                Some(state) if is_editable(section, state) => {
+                    SyncAction::Edit(section, state)
+                },
+                Some(state) => SyncAction::Resend(section, state),
+                None => SyncAction::Send(section)
+            }
+        })
+        .collect::<Vec<SyncAction>>();
+    if prune {
+        sent
+            .iter()
+            .filter( |state| !keys.contains(&state.key) )
+            .for_each( |state| actions.push(SyncAction::Delete(state)) );
+    }
+    return actions;
+}
+
+/// Delete the sent message and mark it as deleted in the ledger.
+async fn delete(telegram: &Telegram, ledger: &mut Ledger, state: &SentState) -> Result<(), String> {
+    for part in &state.parts {
+        telegram.delete_message(state.chat_id, *part).await?;
+    }
+    telegram.delete_message(state.chat_id, state.message_id).await?;
+    ledger.mark_deleted(state.chat_id, state.message_id);
+    return Ok(());
+}
+
+pub async fn main(command: &ArgMatches) {
+    let logger = Log::new("sync");
+    let file = command.get_one::<String>("FILE").unwrap();
+    let prune = command.get_flag("prune");
+    let dry_run = command.get_flag("dry-run");
+    let mut abstraction_tree = parser::parse_file(file, &logger);
+    // The keys of all sections, so the filtered sections will not be pruned.
+    let keys = abstraction_tree.mcqps
+        .iter()
+        .map( |section| section.key() )
+        .collect::<Vec<String>>();
+    Filter::new(command).apply(&mut abstraction_tree);
+    let mut send_config = config::Config::new();
+    send_config.get_config();
+    let telegram = Telegram::new(&send_config.bot_token);
+    let mut ledger = Ledger::load();
+    let file_path = file::absolute(file);
+    let sent = ledger
+        .live_records(&file_path, &send_config.chat_id)
+        .iter()
+        .map( |record| SentState {
+            key: record.key(),
+            hash: record.hash.clone(),
+            kind: record.kind.clone(),
+            chat_id: record.chat_id,
+            message_id: record.message_id,
+            parts: record.parts.clone(),
+            flags: record.flags.clone()
+        })
+        .collect::<Vec<SentState>>();
+    if sent.is_empty() {
+        logger.info("This file was not sent to the chat before, all sections will be sent.");
+    }
+    let actions = plan(&abstraction_tree.mcqps, &sent, &keys, prune);
+    let unchanged = actions.iter().filter( |action| matches!(action, SyncAction::Keep(_)) ).count();
+    let removed = sent.iter().filter( |state| !keys.contains(&state.key) ).count();
+    let mut run_id: Option<u64> = None;
+    // The last message of the previous section, the `reply_to: previous` messages reply to it.
+    let mut previous: Option<i64> = None;
+    for action in &actions {
+        let options = send::SendOptions { previous, ..Default::default() };
+        match action {
+            SyncAction::Keep(state) => previous = Some(state.message_id),
+            SyncAction::Edit(section, state) => {
+                logger.info(&format!("edit the message {}", section.key()));
+                // The edited message keeps its id.
+                previous = Some(state.message_id);
+                if dry_run { continue; }
+                let edit_result = telegram.request::<_, SentMessage>("editMessageText", &EditMessageDto {
+                    chat_id: state.chat_id,
+                    message_id: state.message_id,
+                    text: section.message.as_ref().unwrap().m.clone(),
+                    parse_mode: section.message.as_ref().unwrap().parse_mode.api_name(),
+                    link_preview_options: send::LinkPreviewDto::new(section.message.as_ref().unwrap().is_nopreview),
+                    reply_markup: send::ReplyMarkupDto::new(&section.message.as_ref().unwrap().buttons)
+                }).await;
+                match edit_result {
+                    Ok(edited) => ledger.mark_edited(send::record(section, edited)),
+                    Err(err) => logger.warn(&format!("Can NOT edit the message {}! {}", section.key(), err))
+                }
+            },
+            SyncAction::Resend(section, state) => {
+                logger.info(&format!("delete and resend the {} {}", section._type.name(), section.key()));
+                if dry_run { continue; }
+                if let Err(err) = delete(&telegram, &mut ledger, state).await {
+                    logger.warn(&format!("Can NOT delete the {} {}, it will not be resent! {}", state.kind, state.key, err));
+                    continue;
+                }
+                match send::send_section(&telegram, &send_config.chat_id, section, &options).await {
+                    Ok(sent) => {
+                        previous = Some(sent.message_id);
+                        let run_id = *run_id.get_or_insert_with(|| ledger.new_run(&file_path, &send_config.chat_id, None));
+                        ledger.record(run_id, send::record(section, sent));
+                    },
+                    Err(err) => logger.error(&format!("Can NOT send the {} {}! {}", section._type.name(), section.key(), err))
+                }
+            },
+            SyncAction::Send(section) => {
+                logger.info(&format!("send the new {} {}", section._type.name(), section.key()));
+                if dry_run { continue; }
+                match send::send_section(&telegram, &send_config.chat_id, section, &options).await {
+                    Ok(sent) => {
+                        previous = Some(sent.message_id);
+                        let run_id = *run_id.get_or_insert_with(|| ledger.new_run(&file_path, &send_config.chat_id, None));
+                        ledger.record(run_id, send::record(section, sent));
+                    },
+                    Err(err) => logger.error(&format!("Can NOT send the {} {}! {}", section._type.name(), section.key(), err))
+                }
+            },
+            SyncAction::Delete(state) => {
+                logger.info(&format!("delete the removed {} {}", state.kind, state.key));
+                if dry_run { continue; }
+                if let Err(err) = delete(&telegram, &mut ledger, state).await {
+                    logger.warn(&format!("Can NOT delete the {} {}! {}", state.kind, state.key, err));
+                }
+            }
+        }
+    }
+    logger.info(&format!("{} sections did not change.", unchanged));
+    if removed > 0 && !prune {
+        logger.info(&format!("{} sent sections are not in the file anymore, use `--prune` to delete them.", removed));
+    }
+    if let Some(run_id) = run_id {
+        logger.info(&format!("The synced sections are recorded as run #{}.", run_id));
+    }
+}
@@ -0,0 +1,129 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use reqwest::{Client, Response, multipart::Form};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::json;
+
+/// The updates that mcqp uses, every `getUpdates` call passes all of them, 
+/// because telegram keeps the last `allowed_updates` of the bot and drops 
+/// the other updates.
+pub const ALLOWED_UPDATES: [&str; 2] = ["message", "poll_answer"];
+
+/// The bot API response.
+#[derive(Deserialize)]
+struct BotResDto<T> {
+    /// The request state.
+    ok: bool,
+    /// The request result if `ok` is true.
+    result: Option<T>,
+    /// The error description if `ok` is false.
+    description: Option<String>
+}
+
+/// The sent message (`sendMessage` and `sendPoll` result).
+#[derive(Deserialize)]
+pub struct SentMessage {
+    /// The message id in the chat.
+    pub message_id: i64,
+    /// The chat that the message was sent to.
+    pub chat: SentChat,
+    /// The sent poll if the message is a poll.
+    pub poll: Option<SentPoll>,
+    /// The message ids of the previous messages of the section (the parts of the
+    /// split message or the overflow message), it is NOT a Telegram field.
+    #[serde(skip)]
+    pub parts: Vec<i64>
+}
+
+/// The chat of the sent message.
+#[derive(Deserialize)]
+pub struct SentChat {
+    /// The chat id.
+    pub id: i64
+}
+
+/// The poll of the sent message.
+#[derive(Deserialize)]
+pub struct SentPoll {
+    /// The poll id, the `poll_answer` updates use it.
+    pub id: String
+}
+
+/// The telegram bot API client.
+pub struct Telegram {
+    client: Client,
+    bot_token: String
+}
+
+impl Telegram {
+    pub fn new(bot_token: &str) -> Self {
+        return Self {
+            client: Client::new(),
+            bot_token: bot_token.to_string()
+        };
+    }
+
+    /// Send a post request to the bot API method with the JSON body, it will 
+    /// return the method result or the error description.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let telegram = Telegram::new(&send_config.bot_token);
+    /// let sent = telegram.request::<_, SentMessage>(
+    ///     "sendMessage", 
+    ///     &json!({"chat_id": send_config.chat_id, "text": "Hi"})
+    /// ).await?;
+    /// println!("{}", sent.message_id);
+    /// ```
+    pub async fn request<T: Serialize, R: DeserializeOwned>(&self, method: &str, body: &T) -> Result<R, String> {
+        let res = self.client
+            .post(format!("https://api.telegram.org/bot{}/{}", self.bot_token, method))
+            .json(body)
+            .send()
+            .await
+            .map_err(|_| "Network error, can NOT make a post request!".to_string())?;
+        return Self::result(res).await;
+    }
+
+    /// Send a multipart post request to the bot API method, it is used to upload 
+    /// the local files. It will return the method result or the error description.
+    /// 
+    /// ### Example:
+    /// ```
+    /// let form = Form::new()
+    ///     .text("chat_id", send_config.chat_id.clone())
+    ///     .part("photo", Part::bytes(std::fs::read("./diagram.png")?).file_name("diagram.png"));
+    /// let sent = telegram.upload::<SentMessage>("sendPhoto", form).await?;
+    /// ```
+    pub async fn upload<R: DeserializeOwned>(&self, method: &str, form: Form) -> Result<R, String> {
+        let res = self.client
+            .post(format!("https://api.telegram.org/bot{}/{}", self.bot_token, method))
+            .multipart(form)
+            .send()
+            .await
+            .map_err(|_| "Network error, can NOT make a post request!".to_string())?;
+        return Self::result(res).await;
+    }
+
+    /// Return the result of the bot API response or the error description.
+    async fn result<R: DeserializeOwned>(res: Response) -> Result<R, String> {
+        let res_json = res
+            .json::<BotResDto<R>>()
+            .await
+            .map_err(|_| "Can NOT parse the response!".to_string())?;
+        if !res_json.ok {
+            return Err(res_json.description.unwrap_or("Unknown error!".to_string()));
+        }
+        return res_json.result.ok_or("The response has no result!".to_string());
+    }
+
+    /// Delete the message from the chat.
+    pub async fn delete_message(&self, chat_id: i64, message_id: i64) -> Result<(), String> {
+        self.request::<_, bool>(
+            "deleteMessage", 
+            &json!({"chat_id": chat_id, "message_id": message_id})
+        ).await?;
+        return Ok(());
+    }
+}
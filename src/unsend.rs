@@ -0,0 +1,79 @@
+// This file is part of mcqp project, licensed under the GPL v3.
+// See the LICENSE file for full license details.
+
+use clap::ArgMatches;
+
+use crate::config;
+use crate::display::Display;
+use crate::ledger::Ledger;
+use crate::log::Log;
+use crate::telegram::Telegram;
+use crate::utils;
+
+/// Delete all messages and polls of a send run from the chat.
+pub async fn main(command: &ArgMatches) {
+    let logger = Log::new("unsend");
+    let mut ledger = Ledger::load();
+    let run_id = if command.get_flag("last") {
+        // The runs that failed before sending any section are skipped.
+        ledger.runs
+            .iter()
+            .rev()
+            .find( |run| !run.records.is_empty() )
+            .map( |run| run.id )
+            .unwrap_or_else(|| logger.error("There is no sent sections!"))
+    } else if let Some(id) = command.get_one::<String>("run") {
+        id.parse::<u64>().unwrap_or_else(|_| logger.error("The run id must be a number!"))
+    } else {
+        logger.error("Please use `--last` or `--run <ID>` to select the run!");
+    };
+    let run = ledger.runs
+        .iter()
+        .find( |run| run.id == run_id )
+        .unwrap_or_else(|| logger.error(&format!("The run #{} is NOT found!", run_id)));
+    let messages = run.records
+        .iter()
+        .filter( |record| !record.deleted )
+        .map( |record| (record.chat_id, record.message_id, record.parts.clone(), record.kind.clone()) )
+        .collect::<Vec<(i64, i64, Vec<i64>, String)>>();
+    if messages.is_empty() {
+        logger.info(&format!("All sections of the run #{} are already deleted.", run_id));
+        return;
+    }
+    Display::run_info(run);
+    if !command.get_flag("yes") {
+        let count = messages.iter().map( |(_, _, parts, _)| parts.len() + 1 ).sum::<usize>();
+        let answer = utils::input(&format!("Delete {} messages from the chat? [y/N]: ", count));
+        if answer.to_lowercase() != "y" {
+            logger.info("Nothing was deleted.");
+            return;
+        }
+    }
+    let mut send_config = config::Config::new();
+    send_config.get_config();
+    let telegram = Telegram::new(&send_config.bot_token);
+    let mut failed: usize = 0;
+    for (chat_id, message_id, parts, kind) in messages {
+        // The previous messages of the section (the split message parts or the overflow message).
+        for part in parts {
+            if let Err(err) = telegram.delete_message(chat_id, part).await {
+                failed += 1;
+                logger.warn(&format!("Can NOT delete the {} part {}! {}", kind, part, err));
+            }
+        }
+        match telegram.delete_message(chat_id, message_id).await {
+            Ok(()) => {
+                ledger.mark_deleted(chat_id, message_id);
+                logger.info(&format!("{} deleted successfully", kind));
+            },
+            Err(err) => {
+                failed += 1;
+                logger.warn(&format!("Can NOT delete the {} {}! {}", kind, message_id, err));
+            }
+        }
+    }
+    if failed > 0 {
+        logger.error(&format!("{} messages can NOT be deleted!", failed));
+    }
+    logger.info(&format!("The run #{} is deleted.", run_id));
+}
@@ -23,3 +23,236 @@ pub fn input(message: &str) -> String {
         .expect("Read line Error!");
     return line.trim().to_owned();
 }
+
+/// Return a stable hash of the text, it is the FNV-1a 64-bit hash 
+/// in hex. Unlike `std::hash`, it will not change between the 
+/// Rust versions, so it can be saved to the data dir.
+/// 
+/// ### Example
+/// ```
+/// assert_eq!(utils::hash("mcqp"), utils::hash("mcqp"));
+/// ```
+pub fn hash(text: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in text.as_bytes() {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    return format!("{:016x}", hash);
+}
+
+/// Return the current unix time in seconds.
+pub fn now() -> u64 {
+    return std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map( |duration| duration.as_secs() )
+        .unwrap_or(0);
+}
+
+/// Format the unix time as `YYYY-MM-DD HH:MM:SS UTC`.
+/// 
+/// ### Example
+/// ```
+/// assert_eq!(utils::format_time(0), "1970-01-01 00:00:00 UTC");
+/// ```
+pub fn format_time(time: u64) -> String {
+    let days = (time / 86400) as i64;
+    let seconds = time % 86400;
+    // The civil from days algorithm by Howard Hinnant.
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    return format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year, month, day,
+        seconds / 3600, (seconds % 3600) / 60, seconds % 60
+    );
+}
+
+/// Return the path of the file in the mcqp data dir (`<DATA-DIR>/mcqp/data/<name>`),
+/// the parent dirs will be created if they are not exist.
+pub fn data_path(name: &str) -> std::path::PathBuf {
+    let logger = crate::log::Log::new("data-dir");
+    let path = dirs::data_dir()
+        .unwrap_or_else(|| logger.error("Can NOT get the data dir!"))
+        .join("mcqp/data")
+        .join(name);
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            std::fs::create_dir_all(parent)
+                .unwrap_or_else(|_| logger.error("Can NOT create the data dir!"));
+        }
+    }
+    return path;
+}
+
+/// Write the text to the file atomically, it is written to a temp file in the 
+/// same dir and then renamed, so a crash while writing will NOT truncate the file.
+pub fn write_file(path: &std::path::Path, text: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    let temp_path = path.with_extension(format!("{}.tmp", std::process::id()));
+    let mut file = std::fs::File::create(&temp_path)?;
+    file.write_all(text.as_bytes())?;
+    file.sync_all()?;
+    return std::fs::rename(&temp_path, path);
+}
+
+/// Parse the duration text like `45s`, `10m`, `2h` or `1h30m`, 
+/// the number without unit is seconds.
+/// 
+/// ### Example
+/// ```
+/// assert_eq!(utils::parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+/// assert_eq!(utils::parse_duration("10x"), None);
+/// ```
+pub fn parse_duration(text: &str) -> Option<std::time::Duration> {
+    let mut seconds: u64 = 0;
+    let mut number = String::new();
+    for c in text.trim().chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            's' => 1,
+            'm' => 60,
+            'h' => 3600,
+            'd' => 86400,
+            _ => return None
+        };
+        seconds += number.parse::<u64>().ok()? * unit;
+        number.clear();
+    }
+    if !number.is_empty() {
+        seconds += number.parse::<u64>().ok()?;
+    } else if seconds == 0 {
+        return None;
+    }
+    return Some(std::time::Duration::from_secs(seconds));
+}
+
+/// Return the number of the days in the month.
+fn days_in_month(year: i64, month: i64) -> i64 {
+    return match month {
+        2 if (year % 4 == 0 && year % 100 != 0) || year % 400 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31
+    };
+}
+
+/// Parse the date and time `YYYY-MM-DDTHH:MM[:SS]` to unix time, the time must 
+/// end with an offset like `+03:00`, `-05:00` or `Z` (UTC). The time without an 
+/// offset is rejected, it would be sent at the wrong hour outside UTC.
+/// 
+/// ### Example
+/// ```
+/// assert_eq!(utils::parse_time("1970-01-01T01:00+01:00"), Some(0));
+/// assert_eq!(utils::parse_time("2026-11-02 08:00Z"), Some(1793606400));
+/// assert_eq!(utils::parse_time("2026-11-02T08:00"), None);
+/// assert_eq!(utils::parse_time("2026-02-31T08:00Z"), None);
+/// ```
+pub fn parse_time(text: &str) -> Option<u64> {
+    let text = text.trim();
+    let (date, time) = text.split_once(['T', ' '])?;
+    let date = date.split('-').map( |part| part.parse::<i64>().ok() ).collect::<Option<Vec<i64>>>()?;
+    let [year, month, day] = date[..] else { return None; };
+    // Split the offset from the time.
+    let (time, offset) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+        (time, 0)
+    } else if let Some(index) = time.find(['+', '-']) {
+        let (hours, minutes) = time[index + 1..].split_once(':')?;
+        let (hours, minutes) = (hours.parse::<i64>().ok()?, minutes.parse::<i64>().ok()?);
+        if hours > 14 || minutes > 59 {
+            return None;
+        }
+        let offset = hours * 3600 + minutes * 60;
+        (&time[..index], if time[index..].starts_with('-') { -offset } else { offset })
+    } else {
+        return None;
+    };
+    let time = time.split(':').map( |part| part.parse::<i64>().ok() ).collect::<Option<Vec<i64>>>()?;
+    let (hour, minute, second) = match time[..] {
+        [hour, minute] => (hour, minute, 0),
+        [hour, minute, second] => (hour, minute, second),
+        _ => return None
+    };
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) 
+        || hour > 23 || minute > 59 || second > 59 {
+        return None;
+    }
+    // The days from civil algorithm by Howard Hinnant.
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    let time = days * 86400 + hour * 3600 + minute * 60 + second - offset;
+    return u64::try_from(time).ok();
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("45s"), Some(std::time::Duration::from_secs(45)));
+        assert_eq!(parse_duration("10m"), Some(std::time::Duration::from_secs(600)));
+        assert_eq!(parse_duration("1h30m"), Some(std::time::Duration::from_secs(5400)));
+        assert_eq!(parse_duration("2d"), Some(std::time::Duration::from_secs(172800)));
+        assert_eq!(parse_duration(" 90 "), Some(std::time::Duration::from_secs(90)));
+    }
+
+    #[test]
+    fn parse_duration_invalid() {
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("10x"), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("1.5h"), None);
+    }
+
+    #[test]
+    fn parse_time_offsets() {
+        assert_eq!(parse_time("1970-01-01T01:00+01:00"), Some(0));
+        assert_eq!(parse_time("2026-11-02T08:00Z"), Some(1793606400));
+        assert_eq!(parse_time("2026-11-02 08:00z"), Some(1793606400));
+        assert_eq!(parse_time("2026-11-02T11:00+03:00"), Some(1793606400));
+        assert_eq!(parse_time("2026-11-02T03:00-05:00"), Some(1793606400));
+        assert_eq!(parse_time("2026-11-02T08:00:30Z"), Some(1793606430));
+    }
+
+    #[test]
+    fn parse_time_invalid() {
+        // The time without an offset.
+        assert_eq!(parse_time("2026-11-02T08:00"), None);
+        // The invalid dates and times.
+        assert_eq!(parse_time("2026-02-29T08:00Z"), None);
+        assert_eq!(parse_time("2026-04-31T08:00Z"), None);
+        assert_eq!(parse_time("2026-13-01T08:00Z"), None);
+        assert_eq!(parse_time("2026-11-02T24:00Z"), None);
+        assert_eq!(parse_time("2026-11-02T08:60Z"), None);
+        // The invalid offsets.
+        assert_eq!(parse_time("2026-11-02T08:00+15:00"), None);
+        assert_eq!(parse_time("2026-11-02T08:00+03:60"), None);
+        // The time before the unix epoch.
+        assert_eq!(parse_time("1969-12-31T23:59Z"), None);
+        assert_eq!(parse_time("tomorrow"), None);
+    }
+
+    #[test]
+    fn parse_time_leap_years() {
+        assert_eq!(parse_time("2024-02-29T00:00Z"), Some(1709164800));
+        assert!(parse_time("2000-02-29T00:00Z").is_some());
+        assert_eq!(parse_time("2100-02-29T00:00Z"), None);
+    }
+}